@@ -0,0 +1,81 @@
+//! Times sliding-attack lookups and a perft that leans on them.
+//!
+//! Compare the backends by running it with and without the `pext` feature:
+//!
+//! ```text
+//! cargo bench --bench sliders
+//! cargo bench --bench sliders --features pext
+//! RUSTFLAGS="-C target-cpu=native" cargo bench --bench sliders --features pext
+//! ```
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use chess::attacks::{bishop_attacks, rook_attacks};
+use chess::perft::perft;
+use chess::repr::board::square::Square;
+use chess::repr::board::BitBoard;
+use chess::repr::position::Position;
+
+const ROUNDS: usize = 200;
+
+/// A fixed xorshift sequence of sparse occupancies, like those of real positions.
+fn occupancies() -> Vec<BitBoard> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    (0..1024)
+        .map(|_| {
+            let mut next = || {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state
+            };
+            BitBoard::new(next() & next())
+        })
+        .collect()
+}
+
+/// The fastest of `runs` timings of `f`.
+fn best_of(runs: usize, mut f: impl FnMut()) -> Duration {
+    (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let backend = if cfg!(feature = "pext") {
+        "pext"
+    } else {
+        "magic"
+    };
+    let occupancies = occupancies();
+    let lookups = (ROUNDS * occupancies.len() * Square::ALL.len() * 2) as f64;
+    let elapsed = best_of(5, || {
+        for _ in 0..ROUNDS {
+            for &occupied in &occupancies {
+                for square in Square::ALL {
+                    black_box(rook_attacks(black_box(square), black_box(occupied)));
+                    black_box(bishop_attacks(black_box(square), black_box(occupied)));
+                }
+            }
+        }
+    });
+    println!(
+        "{backend}: {:.2} ns per slider lookup",
+        elapsed.as_nanos() as f64 / lookups
+    );
+
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let mut position: Position = kiwipete.parse().unwrap();
+    let mut nodes = 0;
+    let elapsed = best_of(3, || nodes = perft(black_box(&mut position), 5));
+    println!(
+        "{backend}: kiwipete perft 5, {nodes} nodes in {:.0} ms ({:.1} Mnps)",
+        elapsed.as_secs_f64() * 1e3,
+        nodes as f64 / elapsed.as_secs_f64() / 1e6
+    );
+}
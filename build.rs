@@ -0,0 +1,242 @@
+//! Generates the sliding-piece attack tables.
+//!
+//! Searching for magic numbers is too slow to do on every lookup and too tedious to do by
+//! hand, so it happens here, once per build. The search is seeded with a fixed value so every
+//! build emits the same tables.
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+
+/// A xorshift64* generator; good enough for magic candidates and fully reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Magic numbers with few set bits are found much faster.
+    fn sparse(&mut self) -> u64 {
+        self.next() & self.next() & self.next()
+    }
+}
+
+/// The squares attacked from `square` along `directions`, stopping at the first occupied
+/// square of each ray.
+fn ray_attacks(square: u8, occupied: u64, directions: &[(i8, i8)]) -> u64 {
+    let mut attacks = 0;
+    for &(df, dr) in directions {
+        let (mut file, mut rank) = ((square % 8) as i8, (square / 8) as i8);
+        loop {
+            file += df;
+            rank += dr;
+            if !(0..8).contains(&file) || !(0..8).contains(&rank) {
+                break;
+            }
+            let bit = 1u64 << (rank * 8 + file);
+            attacks |= bit;
+            if occupied & bit != 0 {
+                break;
+            }
+        }
+    }
+    attacks
+}
+
+/// The squares whose occupancy can change the attacks from `square`: every attacked square
+/// on an empty board except the last one of each ray.
+fn relevant_mask(square: u8, directions: &[(i8, i8)]) -> u64 {
+    let mut mask = 0;
+    for &(df, dr) in directions {
+        let (mut file, mut rank) = ((square % 8) as i8, (square / 8) as i8);
+        loop {
+            file += df;
+            rank += dr;
+            let (next_file, next_rank) = (file + df, rank + dr);
+            if !(0..8).contains(&next_file) || !(0..8).contains(&next_rank) {
+                break;
+            }
+            mask |= 1u64 << (rank * 8 + file);
+        }
+    }
+    mask
+}
+
+/// Every subset of `mask`, in carry-rippler order.
+fn subsets(mask: u64) -> Vec<u64> {
+    let mut subsets = Vec::with_capacity(1 << mask.count_ones());
+    let mut subset = 0u64;
+    loop {
+        subsets.push(subset);
+        subset = subset.wrapping_sub(mask) & mask;
+        if subset == 0 {
+            break;
+        }
+    }
+    subsets
+}
+
+/// Emulates the BMI2 `pext` instruction.
+fn pext(value: u64, mut mask: u64) -> u64 {
+    let mut result = 0;
+    let mut bit = 0;
+    while mask != 0 {
+        if value & mask & mask.wrapping_neg() != 0 {
+            result |= 1 << bit;
+        }
+        mask &= mask - 1;
+        bit += 1;
+    }
+    result
+}
+
+struct Slider {
+    name: &'static str,
+    masks: Vec<u64>,
+    magics: Vec<u64>,
+    offsets: Vec<usize>,
+    attacks: Vec<u64>,
+    pext_attacks: Vec<u64>,
+}
+
+fn find_magics(name: &'static str, directions: &[(i8, i8)], rng: &mut Rng) -> Slider {
+    let mut slider = Slider {
+        name,
+        masks: Vec::with_capacity(64),
+        magics: Vec::with_capacity(64),
+        offsets: Vec::with_capacity(64),
+        attacks: Vec::new(),
+        pext_attacks: Vec::new(),
+    };
+
+    for square in 0..64 {
+        let mask = relevant_mask(square, directions);
+        let bits = mask.count_ones();
+        let occupancies = subsets(mask);
+        let references: Vec<u64> = occupancies
+            .iter()
+            .map(|&occ| ray_attacks(square, occ, directions))
+            .collect();
+
+        let size = 1usize << bits;
+        let mut table = vec![0u64; size];
+        let mut epoch = vec![0u32; size];
+        let mut attempt = 0u32;
+        let magic = loop {
+            let candidate = rng.sparse();
+            if (mask.wrapping_mul(candidate) >> 56).count_ones() < 6 {
+                continue;
+            }
+            attempt += 1;
+            let fits = occupancies.iter().zip(&references).all(|(&occ, &attacks)| {
+                let idx = (occ.wrapping_mul(candidate) >> (64 - bits)) as usize;
+                if epoch[idx] < attempt {
+                    epoch[idx] = attempt;
+                    table[idx] = attacks;
+                    true
+                } else {
+                    table[idx] == attacks
+                }
+            });
+            if fits {
+                break candidate;
+            }
+        };
+
+        let mut pext_table = vec![0u64; size];
+        for (&occ, &attacks) in occupancies.iter().zip(&references) {
+            pext_table[pext(occ, mask) as usize] = attacks;
+        }
+
+        slider.masks.push(mask);
+        slider.magics.push(magic);
+        slider.offsets.push(slider.attacks.len());
+        slider.attacks.extend(table);
+        slider.pext_attacks.extend(pext_table);
+    }
+    slider
+}
+
+fn emit_table(out: &mut String, name: &str, values: &[u64]) {
+    writeln!(out, "static {name}: [u64; {}] = [", values.len()).unwrap();
+    for chunk in values.chunks(4) {
+        out.push_str("   ");
+        for value in chunk {
+            write!(out, " {value:#018x},").unwrap();
+        }
+        out.push('\n');
+    }
+    out.push_str("];\n");
+}
+
+fn emit(out: &mut String, slider: &Slider) {
+    let upper = slider.name.to_uppercase();
+    writeln!(out, "const {upper}_MAGICS: [Magic; 64] = [").unwrap();
+    for square in 0..64 {
+        writeln!(
+            out,
+            "    Magic {{ mask: {:#018x}, magic: {:#018x}, shift: {}, offset: {} }},",
+            slider.masks[square],
+            slider.magics[square],
+            64 - slider.masks[square].count_ones(),
+            slider.offsets[square],
+        )
+        .unwrap();
+    }
+    out.push_str("];\n");
+    emit_table(out, &format!("{upper}_ATTACKS"), &slider.attacks);
+}
+
+/// The `between` and `line` tables, indexed by `64 * a + b`.
+fn lines() -> (Vec<u64>, Vec<u64>) {
+    let mut between = vec![0u64; 64 * 64];
+    let mut line = vec![0u64; 64 * 64];
+    for a in 0..64u8 {
+        for directions in [&ROOK_DIRECTIONS, &BISHOP_DIRECTIONS] {
+            let from_a = ray_attacks(a, 0, directions);
+            for b in (0..64u8).filter(|&b| from_a & 1 << b != 0) {
+                let (a_bb, b_bb) = (1u64 << a, 1u64 << b);
+                let idx = 64 * a as usize + b as usize;
+                line[idx] = (from_a & ray_attacks(b, 0, directions)) | a_bb | b_bb;
+                between[idx] = ray_attacks(a, b_bb, directions) & ray_attacks(b, a_bb, directions);
+            }
+        }
+    }
+    (between, line)
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let rook = find_magics("rook", &ROOK_DIRECTIONS, &mut rng);
+    let bishop = find_magics("bishop", &BISHOP_DIRECTIONS, &mut rng);
+
+    let mut out = String::from("// @generated by build.rs; do not edit.\n");
+    emit(&mut out, &rook);
+    emit(&mut out, &bishop);
+
+    let out_dir = env::var_os("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("sliders.rs"), out).unwrap();
+
+    let (between, line) = lines();
+    let mut out = String::from("// @generated by build.rs; do not edit.\n");
+    emit_table(&mut out, "BETWEEN", &between);
+    emit_table(&mut out, "LINE", &line);
+    fs::write(Path::new(&out_dir).join("lines.rs"), out).unwrap();
+
+    if env::var_os("CARGO_FEATURE_PEXT").is_some() {
+        // PEXT indexing yields tables of the same size per square, so the magic offsets apply
+        let mut out = String::from("// @generated by build.rs; do not edit.\n");
+        emit_table(&mut out, "ROOK_PEXT_ATTACKS", &rook.pext_attacks);
+        emit_table(&mut out, "BISHOP_PEXT_ATTACKS", &bishop.pext_attacks);
+        fs::write(Path::new(&out_dir).join("pext_sliders.rs"), out).unwrap();
+    }
+}
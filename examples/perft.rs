@@ -0,0 +1,48 @@
+//! Count the leaf nodes of the move tree of a position, divided by first move.
+//!
+//! Usage: `perft <depth> [fen]`, with a depth of at least 1, as depth 0 has no first moves to
+//! divide by. The output matches the `go perft` divide format of common
+//! engines, so the two can be diffed to find move generation bugs. The time taken and nodes
+//! per second go to standard error.
+use std::env;
+use std::process;
+use std::time::Instant;
+
+use chess::perft::divide;
+use chess::repr::position::Position;
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let Some(depth) = args
+        .next()
+        .and_then(|depth| depth.parse::<u32>().ok())
+        .filter(|&depth| depth > 0)
+    else {
+        eprintln!("usage: perft <depth> [fen], with depth at least 1");
+        process::exit(2);
+    };
+    let fen = args.collect::<Vec<_>>().join(" ");
+    let mut position = if fen.is_empty() {
+        Position::startpos()
+    } else {
+        match fen.parse::<Position>() {
+            Ok(position) => position,
+            Err(err) => {
+                eprintln!("invalid FEN: {err}");
+                process::exit(2);
+            }
+        }
+    };
+
+    let start = Instant::now();
+    let divided = divide(&mut position, depth);
+    let elapsed = start.elapsed();
+    let nodes: u64 = divided.iter().map(|(_, nodes)| nodes).sum();
+    for (mv, nodes) in &divided {
+        println!("{mv}: {nodes}");
+    }
+    println!();
+    println!("Nodes searched: {nodes}");
+    let nps = nodes as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    eprintln!("Time: {:.3}s ({:.0} nodes/s)", elapsed.as_secs_f64(), nps);
+}
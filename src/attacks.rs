@@ -0,0 +1,187 @@
+//! Attack generation.
+//!
+//! The functions in this module return the squares a piece standing on a given square
+//! attacks, as a [BitBoard]. Every lookup is a table access; the tables are computed ahead of
+//! time.
+//!
+//! Sliding attacks use magic bitboards. With the `pext` feature enabled, x86_64 CPUs that
+//! support BMI2 use `pext` indexing instead, falling back to magic bitboards at runtime when
+//! the instruction is unavailable.
+//!
+//! `pext` only pays off when BMI2 is enabled at compile time, e.g. with
+//! `-C target-cpu=native`: the lookups then inline and, in `benches/sliders.rs`, take about
+//! 0.9 ns against 1.2 ns for magic bitboards. Detected at runtime, they cannot inline into
+//! code compiled without BMI2, and the call costs more than the multiplication it saves.
+mod leapers;
+mod lines;
+mod magic;
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+mod pext;
+
+use crate::repr::board::square::Square;
+use crate::repr::board::BitBoard;
+use crate::repr::piece::{Piece, PieceKind};
+
+pub use leapers::{king_attacks, knight_attacks, pawn_attacks};
+pub use lines::{aligned, between, line};
+
+/// The squares a rook on `square` attacks, given the `occupied` squares of the board.
+///
+/// The first occupied square of each ray is included, whichever side the piece on it
+/// belongs to.
+///
+/// # Examples
+///
+/// ```
+/// use chess::attacks::rook_attacks;
+/// use chess::repr::board::square::Square;
+/// use chess::repr::board::BitBoard;
+/// let blockers: BitBoard = [Square::A4, Square::C1].into_iter().collect();
+/// let attacks = rook_attacks(Square::A1, blockers);
+/// let expected: BitBoard = [Square::A2, Square::A3, Square::A4, Square::B1, Square::C1]
+///     .into_iter()
+///     .collect();
+/// assert_eq!(attacks, expected);
+/// ```
+#[inline]
+pub fn rook_attacks(square: Square, occupied: BitBoard) -> BitBoard {
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    if pext::available() {
+        // SAFETY: BMI2 support was checked
+        return unsafe { pext::rook_attacks(square, occupied) };
+    }
+    magic::rook_attacks(square, occupied)
+}
+
+/// The squares a bishop on `square` attacks, given the `occupied` squares of the board.
+///
+/// The first occupied square of each ray is included, whichever side the piece on it
+/// belongs to.
+#[inline]
+pub fn bishop_attacks(square: Square, occupied: BitBoard) -> BitBoard {
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    if pext::available() {
+        // SAFETY: BMI2 support was checked
+        return unsafe { pext::bishop_attacks(square, occupied) };
+    }
+    magic::bishop_attacks(square, occupied)
+}
+
+/// The squares a queen on `square` attacks, given the `occupied` squares of the board.
+#[inline]
+pub fn queen_attacks(square: Square, occupied: BitBoard) -> BitBoard {
+    rook_attacks(square, occupied) | bishop_attacks(square, occupied)
+}
+
+/// The squares `piece` on `square` attacks, given the `occupied` squares of the board.
+#[inline]
+pub fn piece_attacks(piece: Piece, square: Square, occupied: BitBoard) -> BitBoard {
+    match piece.kind() {
+        PieceKind::Pawn => pawn_attacks(piece.color(), square),
+        PieceKind::Knight => knight_attacks(square),
+        PieceKind::Bishop => bishop_attacks(square, occupied),
+        PieceKind::Rook => rook_attacks(square, occupied),
+        PieceKind::Queen => queen_attacks(square, occupied),
+        PieceKind::King => king_attacks(square),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repr::board::Direction;
+
+    /// Walks each ray from `square` until it leaves the board or hits an occupied square.
+    fn ray_attacks(square: Square, occupied: BitBoard, directions: &[Direction]) -> BitBoard {
+        let mut attacks = BitBoard::default();
+        for direction in directions {
+            let (df, dr) = direction.delta();
+            let mut current = square;
+            while let Some(next) = current.offset(df, dr) {
+                attacks.set(next.into());
+                if occupied.contains(next) {
+                    break;
+                }
+                current = next;
+            }
+        }
+        attacks
+    }
+
+    /// A fixed xorshift generator, so failures are reproducible.
+    fn occupancies() -> impl Iterator<Item = BitBoard> {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        std::iter::repeat_with(move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        })
+        .flat_map(|r| [BitBoard::new(r), BitBoard::new(r & (r >> 8) & (r >> 16))])
+        .take(400)
+    }
+
+    #[test]
+    fn rook_attacks_match_ray_walk() {
+        for square in Square::ALL {
+            for occupied in occupancies().chain([BitBoard::EMPTY, BitBoard::FULL]) {
+                assert_eq!(
+                    rook_attacks(square, occupied),
+                    ray_attacks(square, occupied, &Direction::ORTHOGONAL),
+                    "{square}\n{occupied}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bishop_attacks_match_ray_walk() {
+        for square in Square::ALL {
+            for occupied in occupancies().chain([BitBoard::EMPTY, BitBoard::FULL]) {
+                assert_eq!(
+                    bishop_attacks(square, occupied),
+                    ray_attacks(square, occupied, &Direction::DIAGONAL),
+                    "{square}\n{occupied}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn queen_attacks_combine_rook_and_bishop() {
+        for square in Square::ALL {
+            for occupied in occupancies() {
+                assert_eq!(
+                    queen_attacks(square, occupied),
+                    ray_attacks(square, occupied, &Direction::ALL),
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    fn pext_matches_magic() {
+        if !pext::available() {
+            eprintln!("BMI2 is not supported by this CPU; skipping");
+            return;
+        }
+        for square in Square::ALL {
+            for occupied in occupancies().chain([BitBoard::EMPTY, BitBoard::FULL]) {
+                // SAFETY: BMI2 support was checked
+                unsafe {
+                    assert_eq!(
+                        pext::rook_attacks(square, occupied),
+                        magic::rook_attacks(square, occupied),
+                        "{square}\n{occupied}"
+                    );
+                    assert_eq!(
+                        pext::bishop_attacks(square, occupied),
+                        magic::bishop_attacks(square, occupied),
+                        "{square}\n{occupied}"
+                    );
+                }
+            }
+        }
+    }
+}
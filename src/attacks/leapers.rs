@@ -0,0 +1,160 @@
+//! Attacks of the pieces that jump to their target squares: knights, kings and pawns.
+use crate::repr::board::square::Square;
+use crate::repr::board::BitBoard;
+use crate::repr::piece::Color;
+
+const KNIGHT_ATTACKS: [BitBoard; 64] = {
+    let mut table = [BitBoard::EMPTY; 64];
+    let mut idx = 0;
+    while idx < 64 {
+        let b = BitBoard::from_square(Square::ALL[idx]);
+        let n = b.north().north();
+        let s = b.south().south();
+        let e = b.east().east();
+        let w = b.west().west();
+        table[idx] = BitBoard::new(
+            n.east().bits()
+                | n.west().bits()
+                | s.east().bits()
+                | s.west().bits()
+                | e.north().bits()
+                | e.south().bits()
+                | w.north().bits()
+                | w.south().bits(),
+        );
+        idx += 1;
+    }
+    table
+};
+
+const KING_ATTACKS: [BitBoard; 64] = {
+    let mut table = [BitBoard::EMPTY; 64];
+    let mut idx = 0;
+    while idx < 64 {
+        let b = BitBoard::from_square(Square::ALL[idx]);
+        let row = BitBoard::new(b.bits() | b.east().bits() | b.west().bits());
+        table[idx] =
+            BitBoard::new((row.bits() | row.north().bits() | row.south().bits()) & !b.bits());
+        idx += 1;
+    }
+    table
+};
+
+const PAWN_ATTACKS: [[BitBoard; 64]; 2] = {
+    let mut table = [[BitBoard::EMPTY; 64]; 2];
+    let mut idx = 0;
+    while idx < 64 {
+        let b = BitBoard::from_square(Square::ALL[idx]);
+        table[Color::White.index()][idx] =
+            BitBoard::new(b.north_east().bits() | b.north_west().bits());
+        table[Color::Black.index()][idx] =
+            BitBoard::new(b.south_east().bits() | b.south_west().bits());
+        idx += 1;
+    }
+    table
+};
+
+/// The squares a knight on `square` attacks.
+///
+/// # Examples
+///
+/// ```
+/// use chess::attacks::knight_attacks;
+/// use chess::repr::board::square::Square;
+/// assert_eq!(knight_attacks(Square::A1).population_count(), 2);
+/// assert_eq!(knight_attacks(Square::D4).population_count(), 8);
+/// ```
+#[inline]
+pub const fn knight_attacks(square: Square) -> BitBoard {
+    KNIGHT_ATTACKS[square.index()]
+}
+
+/// The squares a king on `square` attacks.
+#[inline]
+pub const fn king_attacks(square: Square) -> BitBoard {
+    KING_ATTACKS[square.index()]
+}
+
+/// The squares a pawn of `color` on `square` attacks.
+///
+/// These are the squares the pawn could capture on, not the squares it can advance to.
+#[inline]
+pub const fn pawn_attacks(color: Color, square: Square) -> BitBoard {
+    PAWN_ATTACKS[color.index()][square.index()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repr::board::square::{File, Rank};
+
+    /// The squares reached from `square` by each of `offsets`, computed on [File] and [Rank].
+    fn naive_attacks(square: Square, offsets: &[(i8, i8)]) -> BitBoard {
+        offsets
+            .iter()
+            .filter_map(|&(df, dr)| {
+                let file = File::try_from((u8::from(square.file()) as i8 + df) as u8).ok()?;
+                let rank = Rank::try_from((square.rank() as u8 as i8 + dr) as u8).ok()?;
+                Some(Square::new(file, rank))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn knight_attacks_match_naive() {
+        let offsets = [
+            (1, 2),
+            (2, 1),
+            (2, -1),
+            (1, -2),
+            (-1, -2),
+            (-2, -1),
+            (-2, 1),
+            (-1, 2),
+        ];
+        for square in Square::ALL {
+            assert_eq!(
+                knight_attacks(square),
+                naive_attacks(square, &offsets),
+                "{square}"
+            );
+        }
+    }
+
+    #[test]
+    fn king_attacks_match_naive() {
+        let offsets = [
+            (0, 1),
+            (1, 1),
+            (1, 0),
+            (1, -1),
+            (0, -1),
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+        ];
+        for square in Square::ALL {
+            assert_eq!(
+                king_attacks(square),
+                naive_attacks(square, &offsets),
+                "{square}"
+            );
+        }
+    }
+
+    #[test]
+    fn pawn_attacks_match_naive() {
+        for square in Square::ALL {
+            assert_eq!(
+                pawn_attacks(Color::White, square),
+                naive_attacks(square, &[(-1, 1), (1, 1)]),
+                "{square}"
+            );
+            assert_eq!(
+                pawn_attacks(Color::Black, square),
+                naive_attacks(square, &[(-1, -1), (1, -1)]),
+                "{square}"
+            );
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Lines and segments between pairs of squares.
+//!
+//! The tables are generated by the build script from the same rays as the sliding attacks.
+use crate::repr::board::square::Square;
+use crate::repr::board::BitBoard;
+
+include!(concat!(env!("OUT_DIR"), "/lines.rs"));
+
+/// The squares strictly between `a` and `b`, if they share a rank, file or diagonal.
+///
+/// Empty if the squares are not aligned or are adjacent.
+///
+/// # Examples
+///
+/// ```
+/// use chess::attacks::between;
+/// use chess::repr::board::square::Square;
+/// let squares: Vec<Square> = between(Square::B2, Square::E5).into_iter().collect();
+/// assert_eq!(squares, vec![Square::C3, Square::D4]);
+/// assert!(between(Square::A1, Square::B3).is_empty());
+/// ```
+#[inline]
+pub fn between(a: Square, b: Square) -> BitBoard {
+    BitBoard::new(BETWEEN[64 * a.index() + b.index()])
+}
+
+/// The full rank, file or diagonal running through both `a` and `b`, edge to edge.
+///
+/// Empty if the squares are not aligned or are the same square.
+#[inline]
+pub fn line(a: Square, b: Square) -> BitBoard {
+    BitBoard::new(LINE[64 * a.index() + b.index()])
+}
+
+/// Whether `a`, `b` and `c` lie on a single rank, file or diagonal.
+#[inline]
+pub fn aligned(a: Square, b: Square, c: Square) -> bool {
+    line(a, b).contains(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repr::board::masks;
+
+    #[test]
+    fn line_is_symmetric_and_spans_the_board() {
+        assert_eq!(line(Square::A1, Square::H8), masks::MAIN_DIAGONAL);
+        assert_eq!(line(Square::C3, Square::E5), masks::MAIN_DIAGONAL);
+        assert_eq!(line(Square::E2, Square::E7), masks::FILE_E);
+        assert_eq!(line(Square::B4, Square::G4), masks::RANK_4);
+        assert!(line(Square::A1, Square::A1).is_empty());
+        assert!(line(Square::A1, Square::B3).is_empty());
+        for a in Square::ALL {
+            for b in Square::ALL {
+                assert_eq!(line(a, b), line(b, a));
+                assert_eq!(between(a, b), between(b, a));
+                assert_eq!(between(a, b) & !line(a, b), BitBoard::EMPTY);
+            }
+        }
+    }
+
+    #[test]
+    fn between_excludes_endpoints() {
+        assert_eq!(between(Square::A1, Square::A8).population_count(), 6);
+        assert!(!between(Square::A1, Square::A8).contains(Square::A1));
+        assert!(between(Square::D4, Square::E5).is_empty());
+        assert!(between(Square::D4, Square::D4).is_empty());
+        assert_eq!(between(Square::H1, Square::F3).lsb(), Some(Square::G2));
+    }
+
+    #[test]
+    fn aligned_squares() {
+        assert!(aligned(Square::E1, Square::E4, Square::E8));
+        assert!(aligned(Square::A8, Square::H1, Square::D5));
+        assert!(!aligned(Square::E1, Square::E4, Square::D5));
+    }
+}
@@ -0,0 +1,55 @@
+//! Sliding-piece attacks using [fancy magic bitboards].
+//!
+//! For every square, the occupancy of the squares that can block a rook or bishop is hashed
+//! by multiplying it with a magic number, and the top bits of the product index a table of
+//! precomputed attacks. The magic numbers and tables are generated by the build script.
+//!
+//! [fancy magic bitboards]: https://www.chessprogramming.org/Magic_Bitboards#Fancy
+use crate::repr::board::square::Square;
+use crate::repr::board::BitBoard;
+
+/// The magic hashing parameters of one square.
+pub(super) struct Magic {
+    /// The squares whose occupancy changes the attacks.
+    pub(super) mask: u64,
+    magic: u64,
+    shift: u32,
+    /// The start of this square's entries in the attack table.
+    pub(super) offset: usize,
+}
+
+impl Magic {
+    #[inline(always)]
+    fn index(&self, occupied: BitBoard) -> usize {
+        let relevant = occupied.bits() & self.mask;
+        self.offset + (relevant.wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/sliders.rs"));
+
+/// The magic hashing parameters of a rook on `square`.
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+pub(super) fn rook_magic(square: Square) -> &'static Magic {
+    &ROOK_MAGICS[square.index()]
+}
+
+/// The magic hashing parameters of a bishop on `square`.
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+pub(super) fn bishop_magic(square: Square) -> &'static Magic {
+    &BISHOP_MAGICS[square.index()]
+}
+
+/// The squares a rook on `square` attacks, given the `occupied` squares of the board.
+#[inline]
+pub fn rook_attacks(square: Square, occupied: BitBoard) -> BitBoard {
+    let magic = &ROOK_MAGICS[square.index()];
+    BitBoard::new(ROOK_ATTACKS[magic.index(occupied)])
+}
+
+/// The squares a bishop on `square` attacks, given the `occupied` squares of the board.
+#[inline]
+pub fn bishop_attacks(square: Square, occupied: BitBoard) -> BitBoard {
+    let magic = &BISHOP_MAGICS[square.index()];
+    BitBoard::new(BISHOP_ATTACKS[magic.index(occupied)])
+}
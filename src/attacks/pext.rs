@@ -0,0 +1,68 @@
+//! Sliding-piece attacks indexed with the BMI2 `pext` instruction.
+//!
+//! `pext` gathers the occupancy bits under a square's relevant mask into a dense index, which
+//! replaces the multiply and shift of the [magic](super::magic) lookup. The masks and table
+//! offsets are shared with the magic tables.
+use std::arch::x86_64::_pext_u64;
+#[cfg(not(target_feature = "bmi2"))]
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use super::magic;
+use crate::repr::board::square::Square;
+use crate::repr::board::BitBoard;
+
+include!(concat!(env!("OUT_DIR"), "/pext_sliders.rs"));
+
+/// Whether the running CPU supports `pext`.
+///
+/// When compiling for CPUs with BMI2, e.g. with `-C target-cpu=native`, this is known at
+/// compile time and the lookups inline into their callers. Otherwise the CPU is queried once
+/// and the answer cached.
+#[inline(always)]
+pub(super) fn available() -> bool {
+    #[cfg(target_feature = "bmi2")]
+    {
+        true
+    }
+    #[cfg(not(target_feature = "bmi2"))]
+    {
+        const UNKNOWN: u8 = 0;
+        const ABSENT: u8 = 1;
+        const PRESENT: u8 = 2;
+        static BMI2: AtomicU8 = AtomicU8::new(UNKNOWN);
+        match BMI2.load(Ordering::Relaxed) {
+            UNKNOWN => {
+                let present = is_x86_feature_detected!("bmi2");
+                BMI2.store(if present { PRESENT } else { ABSENT }, Ordering::Relaxed);
+                present
+            }
+            state => state == PRESENT,
+        }
+    }
+}
+
+/// The squares a rook on `square` attacks, given the `occupied` squares of the board.
+///
+/// # Safety
+///
+/// The CPU must support BMI2; see [available].
+#[cfg_attr(target_feature = "bmi2", inline(always))]
+#[cfg_attr(not(target_feature = "bmi2"), inline, target_feature(enable = "bmi2"))]
+pub(super) unsafe fn rook_attacks(square: Square, occupied: BitBoard) -> BitBoard {
+    let magic = magic::rook_magic(square);
+    let idx = magic.offset + _pext_u64(occupied.bits(), magic.mask) as usize;
+    BitBoard::new(ROOK_PEXT_ATTACKS[idx])
+}
+
+/// The squares a bishop on `square` attacks, given the `occupied` squares of the board.
+///
+/// # Safety
+///
+/// The CPU must support BMI2; see [available].
+#[cfg_attr(target_feature = "bmi2", inline(always))]
+#[cfg_attr(not(target_feature = "bmi2"), inline, target_feature(enable = "bmi2"))]
+pub(super) unsafe fn bishop_attacks(square: Square, occupied: BitBoard) -> BitBoard {
+    let magic = magic::bishop_magic(square);
+    let idx = magic.offset + _pext_u64(occupied.bits(), magic.mask) as usize;
+    BitBoard::new(BISHOP_PEXT_ATTACKS[idx])
+}
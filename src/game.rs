@@ -0,0 +1,488 @@
+//! Playing a game to its end under the FIDE Laws of Chess.
+//!
+//! A [GameState] keeps the moves played from its starting position and the [Zobrist
+//! hashes](crate::repr::zobrist) of the positions since the last capture or pawn move, so it
+//! can tell repetitions apart from the position alone. It adjudicates the draws that end a
+//! game automatically and the ones a player may claim.
+//!
+//! The history type is named `GameState` rather than `Game` so that it does not clash with
+//! [pgn::Game](crate::pgn::Game), the record of a game read from PGN, when both are in scope.
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::pgn::GameResult;
+use crate::repr::board::masks::{DARK_SQUARES, LIGHT_SQUARES};
+use crate::repr::moves::Move;
+use crate::repr::piece::{Color, PieceKind};
+use crate::repr::position::{Position, UndoInfo};
+use crate::repr::zobrist;
+
+/// Why a game ended.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Termination {
+    /// The side to move is checkmated.
+    Checkmate,
+    /// The side to move has no legal move and is not in check.
+    Stalemate,
+    /// Neither side has the material to checkmate.
+    InsufficientMaterial,
+    /// The same position occurred three times, and a player claimed the draw.
+    ThreefoldRepetition,
+    /// The same position occurred five times.
+    FivefoldRepetition,
+    /// Fifty moves were played by each side without a capture or pawn move, and a player
+    /// claimed the draw.
+    FiftyMoves,
+    /// Seventy-five moves were played by each side without a capture or pawn move.
+    SeventyFiveMoves,
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Termination::Checkmate => "checkmate",
+            Termination::Stalemate => "stalemate",
+            Termination::InsufficientMaterial => "insufficient material",
+            Termination::ThreefoldRepetition => "threefold repetition",
+            Termination::FivefoldRepetition => "fivefold repetition",
+            Termination::FiftyMoves => "fifty-move rule",
+            Termination::SeventyFiveMoves => "seventy-five-move rule",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// How a game ended: who won, if anyone, and why.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GameOutcome {
+    /// The winner, or `None` for a draw.
+    pub winner: Option<Color>,
+    /// Why the game ended.
+    pub termination: Termination,
+}
+
+impl GameOutcome {
+    /// A draw for `termination`.
+    const fn draw(termination: Termination) -> GameOutcome {
+        GameOutcome {
+            winner: None,
+            termination,
+        }
+    }
+
+    /// The PGN result of the outcome.
+    pub fn result(&self) -> GameResult {
+        match self.winner {
+            Some(Color::White) => GameResult::WhiteWins,
+            Some(Color::Black) => GameResult::BlackWins,
+            None => GameResult::Draw,
+        }
+    }
+}
+
+/// A game: a position and the history of moves that led to it.
+///
+/// # Examples
+///
+/// ```
+/// use chess::game::{GameState, Termination};
+/// use chess::repr::position::Position;
+/// let mut game = GameState::new(Position::startpos());
+/// for san in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"] {
+///     game.play(game.position().parse_san(san).unwrap());
+/// }
+/// assert!(game.is_threefold_repetition());
+/// game.undo();
+/// let ng8 = game.position().parse_san("Ng8").unwrap();
+/// assert_eq!(game.claimable_draw(), None);
+/// assert_eq!(game.claimable_draw_with(ng8).unwrap().termination, Termination::ThreefoldRepetition);
+/// game.play(ng8);
+/// assert_eq!(game.outcome(), None);
+/// assert_eq!(game.claimable_draw().unwrap().termination, Termination::ThreefoldRepetition);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameState {
+    position: Position,
+    /// Each move played, with what is needed to take it back: the keys it replaced if it was
+    /// irreversible.
+    moves: Vec<(Move, UndoInfo, Option<Vec<u64>>)>,
+    /// The [repetition keys](repetition_key) of the positions since the last capture or pawn
+    /// move, the current one last.
+    keys: Vec<u64>,
+}
+
+/// The Zobrist hash of `position`, as compared for repetitions.
+///
+/// Polyglot hashes the en passant file whenever a pawn stands ready to capture, but FIDE
+/// only tells positions apart by en passant when the capture is legal.
+fn repetition_key(position: &Position) -> u64 {
+    let en_passant = zobrist::en_passant(position);
+    if en_passant != 0 && !position.legal_moves().iter().any(|mv| mv.is_en_passant()) {
+        position.zobrist_hash() ^ en_passant
+    } else {
+        position.zobrist_hash()
+    }
+}
+
+/// How many times the position with `key` occurs, counting itself, if the positions since
+/// the last capture or pawn move before it had the keys `earlier`, the latest last.
+fn occurrences(key: u64, earlier: &[u64]) -> usize {
+    // the same player is to move every other position
+    1 + earlier
+        .iter()
+        .rev()
+        .skip(1)
+        .step_by(2)
+        .filter(|&&k| k == key)
+        .count()
+}
+
+impl GameState {
+    /// A game without moves from `position`.
+    ///
+    /// Nothing is known of the positions before it, so they never count as repetitions.
+    pub fn new(position: Position) -> GameState {
+        GameState {
+            position,
+            moves: Vec::new(),
+            keys: vec![repetition_key(&position)],
+        }
+    }
+
+    /// The current position.
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// The moves played, in order.
+    pub fn moves(&self) -> impl Iterator<Item = Move> + '_ {
+        self.moves.iter().map(|&(mv, ..)| mv)
+    }
+
+    /// Plays `mv`, which must be legal in the current position.
+    pub fn play(&mut self, mv: Move) {
+        let undo = self.position.make_move(mv);
+        // after a capture or pawn move, no earlier position can occur again
+        let replaced =
+            (self.position.halfmove_clock() == 0).then(|| std::mem::take(&mut self.keys));
+        self.keys.push(repetition_key(&self.position));
+        self.moves.push((mv, undo, replaced));
+    }
+
+    /// Takes back the last move, returning it, or `None` if no move was played.
+    pub fn undo(&mut self) -> Option<Move> {
+        let (mv, undo, replaced) = self.moves.pop()?;
+        self.position.unmake_move(mv, undo);
+        match replaced {
+            Some(keys) => self.keys = keys,
+            None => {
+                self.keys.pop();
+            }
+        }
+        Some(mv)
+    }
+
+    /// How many times the current position has occurred, counting itself.
+    ///
+    /// Positions are the same when the same player is to move, the pieces stand on the same
+    /// squares and the same castling and en passant captures are legal. Only positions since
+    /// the last capture or pawn move are compared, as none before can repeat.
+    pub fn repetitions(&self) -> usize {
+        let (&current, earlier) = self
+            .keys
+            .split_last()
+            .expect("the current position has a key");
+        occurrences(current, earlier)
+    }
+
+    /// Whether the current position has occurred at least three times, letting the player to
+    /// move claim a draw.
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.repetitions() >= 3
+    }
+
+    /// Whether the current position has occurred at least five times, which draws the game.
+    pub fn is_fivefold_repetition(&self) -> bool {
+        self.repetitions() >= 5
+    }
+
+    /// Whether the last fifty moves of each side had no capture or pawn move, letting the
+    /// player to move claim a draw.
+    pub fn can_claim_fifty_moves(&self) -> bool {
+        self.position.halfmove_clock() >= 100
+    }
+
+    /// Whether the last seventy-five moves of each side had no capture or pawn move, which
+    /// draws the game unless the last move checkmated.
+    pub fn is_seventy_five_move_draw(&self) -> bool {
+        self.position.halfmove_clock() >= 150 && !self.is_checkmate()
+    }
+
+    /// Whether neither side can checkmate by any sequence of legal moves, because only kings
+    /// and minor pieces remain and they cannot build a mate.
+    ///
+    /// That is the case with a single minor piece on the board, or with bishops alone all on
+    /// squares of the same color.
+    pub fn is_insufficient_material(&self) -> bool {
+        let board = self.position.board();
+        let heavy_or_pawns = board.pieces(PieceKind::Pawn)
+            | board.pieces(PieceKind::Rook)
+            | board.pieces(PieceKind::Queen);
+        if !heavy_or_pawns.is_empty() {
+            return false;
+        }
+        let knights = board.pieces(PieceKind::Knight);
+        let bishops = board.pieces(PieceKind::Bishop);
+        if knights.is_empty()
+            && ((bishops & LIGHT_SQUARES).is_empty() || (bishops & DARK_SQUARES).is_empty())
+        {
+            return true;
+        }
+        (knights | bishops).population_count() <= 1
+    }
+
+    /// Whether the side to move is checkmated.
+    pub fn is_checkmate(&self) -> bool {
+        self.position.in_check() && self.position.legal_moves().is_empty()
+    }
+
+    /// Whether the side to move has no legal move but is not in check.
+    pub fn is_stalemate(&self) -> bool {
+        !self.position.in_check() && self.position.legal_moves().is_empty()
+    }
+
+    /// How the game has ended without any claim, if it has: by checkmate, stalemate,
+    /// insufficient material, fivefold repetition or the seventy-five-move rule.
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        if self.position.legal_moves().is_empty() {
+            return Some(if self.position.in_check() {
+                GameOutcome {
+                    winner: Some(self.position.side_to_move().opposite()),
+                    termination: Termination::Checkmate,
+                }
+            } else {
+                GameOutcome::draw(Termination::Stalemate)
+            });
+        }
+        let termination = if self.is_insufficient_material() {
+            Termination::InsufficientMaterial
+        } else if self.is_fivefold_repetition() {
+            Termination::FivefoldRepetition
+        } else if self.is_seventy_five_move_draw() {
+            Termination::SeventyFiveMoves
+        } else {
+            return None;
+        };
+        Some(GameOutcome::draw(termination))
+    }
+
+    /// The draw the player to move may claim in the current position, if any: by threefold
+    /// repetition or the fifty-move rule.
+    ///
+    /// For a draw the player's next move would complete, see
+    /// [`GameState::claimable_draw_with`].
+    pub fn claimable_draw(&self) -> Option<GameOutcome> {
+        if self.is_threefold_repetition() {
+            Some(GameOutcome::draw(Termination::ThreefoldRepetition))
+        } else if self.can_claim_fifty_moves() {
+            Some(GameOutcome::draw(Termination::FiftyMoves))
+        } else {
+            None
+        }
+    }
+
+    /// The draw the player to move may claim by declaring `mv`, which must be legal, if it
+    /// completes a threefold repetition or fifty moves without a capture or pawn move.
+    ///
+    /// Under the FIDE Laws, the player writes down the move and claims instead of playing it.
+    pub fn claimable_draw_with(&self, mv: Move) -> Option<GameOutcome> {
+        let mut position = self.position;
+        position.make_move(mv);
+        let earlier = if position.halfmove_clock() == 0 {
+            &[][..]
+        } else {
+            &self.keys[..]
+        };
+        if occurrences(repetition_key(&position), earlier) >= 3 {
+            Some(GameOutcome::draw(Termination::ThreefoldRepetition))
+        } else if position.halfmove_clock() >= 100 {
+            Some(GameOutcome::draw(Termination::FiftyMoves))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(fen: &str, moves: &[&str]) -> GameState {
+        let mut game = GameState::new(fen.parse().unwrap());
+        for san in moves {
+            game.play(game.position().parse_san(san).unwrap());
+        }
+        game
+    }
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    const SHUFFLE: [&str; 4] = ["Nf3", "Nf6", "Ng1", "Ng8"];
+
+    #[test]
+    fn checkmate_and_stalemate() {
+        let mate = game(START, &["f3", "e5", "g4", "Qh4#"]);
+        assert!(mate.is_checkmate() && !mate.is_stalemate());
+        let outcome = mate.outcome().unwrap();
+        assert_eq!(outcome.winner, Some(Color::Black));
+        assert_eq!(outcome.termination, Termination::Checkmate);
+        assert_eq!(outcome.result(), GameResult::BlackWins);
+
+        let stalemate = game("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", &[]);
+        assert!(stalemate.is_stalemate() && !stalemate.is_checkmate());
+        assert_eq!(
+            stalemate.outcome(),
+            Some(GameOutcome::draw(Termination::Stalemate))
+        );
+        assert_eq!(game(START, &[]).outcome(), None);
+    }
+
+    #[test]
+    fn repetitions() {
+        let mut game = game(START, &SHUFFLE);
+        assert_eq!(game.repetitions(), 2);
+        assert!(!game.is_threefold_repetition());
+        for san in SHUFFLE.iter().chain(&SHUFFLE).chain(&SHUFFLE) {
+            game.play(game.position().parse_san(san).unwrap());
+        }
+        assert_eq!(game.repetitions(), 5);
+        assert!(game.is_threefold_repetition() && game.is_fivefold_repetition());
+        assert_eq!(
+            game.outcome(),
+            Some(GameOutcome::draw(Termination::FivefoldRepetition))
+        );
+
+        assert!(game.undo().is_some());
+        assert_eq!(game.repetitions(), 4);
+        assert_eq!(game.outcome(), None);
+        assert_eq!(
+            game.claimable_draw(),
+            Some(GameOutcome::draw(Termination::ThreefoldRepetition))
+        );
+    }
+
+    #[test]
+    fn irreversible_moves_and_rights_break_repetitions() {
+        // the kings return, but castling rights are lost
+        let game = game(START, &["e4", "e5", "Ke2", "Ke7", "Ke1", "Ke8"]);
+        assert_eq!(game.repetitions(), 1);
+
+        // a double push whose en passant capture is possible only the first time
+        let ep = "4k3/8/8/8/5p2/8/4P3/4K3 w - - 0 1";
+        let mut game = self::game(ep, &["e4", "Kd7", "Kd1", "Ke8", "Ke1"]);
+        assert_eq!(game.repetitions(), 1);
+        for san in ["Kd7", "Kd1", "Ke8", "Ke1"] {
+            game.play(game.position().parse_san(san).unwrap());
+        }
+        assert_eq!(game.repetitions(), 2);
+    }
+
+    #[test]
+    fn en_passant_counts_only_when_legal() {
+        // the pawn on e5 is pinned, so exd6 is illegal and the positions are the same
+        let pinned = "4r1k1/3p4/8/4P3/8/8/8/4K3 b - - 0 1";
+        let game = game(pinned, &["d5", "Kd1", "Kh8", "Ke1", "Kg8"]);
+        assert_eq!(game.position().en_passant(), None);
+        assert_eq!(game.repetitions(), 2);
+
+        let free = "6k1/3p4/8/4P3/8/8/8/4K3 b - - 0 1";
+        let game = self::game(free, &["d5", "Kd1", "Kh8", "Ke1", "Kg8"]);
+        assert_eq!(game.repetitions(), 1);
+    }
+
+    #[test]
+    fn history_restarts_at_irreversible_moves() {
+        let mut game = game(START, &SHUFFLE);
+        assert_eq!(game.keys.len(), 5);
+        game.play(game.position().parse_san("e4").unwrap());
+        assert_eq!(game.keys.len(), 1);
+        game.undo();
+        assert_eq!(game.keys.len(), 5);
+        assert_eq!(game.repetitions(), 2);
+        assert_eq!(game.moves().count(), 4);
+    }
+
+    #[test]
+    fn claims_with_the_next_move() {
+        let mut game = game(START, &SHUFFLE);
+        for san in &SHUFFLE[..3] {
+            game.play(game.position().parse_san(san).unwrap());
+        }
+        let ng8 = game.position().parse_san("Ng8").unwrap();
+        let nc6 = game.position().parse_san("Nc6").unwrap();
+        let e5 = game.position().parse_san("e5").unwrap();
+        assert_eq!(game.claimable_draw(), None);
+        assert_eq!(
+            game.claimable_draw_with(ng8),
+            Some(GameOutcome::draw(Termination::ThreefoldRepetition))
+        );
+        assert_eq!(game.claimable_draw_with(nc6), None);
+        assert_eq!(game.claimable_draw_with(e5), None);
+
+        let rooks = self::game("4k3/r7/8/8/8/8/R7/4K3 w - - 99 80", &[]);
+        let rb2 = rooks.position().parse_san("Rb2").unwrap();
+        let rxa7 = rooks.position().parse_san("Rxa7").unwrap();
+        assert_eq!(rooks.claimable_draw(), None);
+        assert_eq!(
+            rooks.claimable_draw_with(rb2),
+            Some(GameOutcome::draw(Termination::FiftyMoves))
+        );
+        assert_eq!(rooks.claimable_draw_with(rxa7), None);
+    }
+
+    #[test]
+    fn move_rules() {
+        let rooks = "4k3/r7/8/8/8/8/R7/4K3 w - - 99 80";
+        let game = self::game(rooks, &["Rb2"]);
+        assert!(game.can_claim_fifty_moves());
+        assert_eq!(
+            game.claimable_draw(),
+            Some(GameOutcome::draw(Termination::FiftyMoves))
+        );
+        assert!(!game.is_seventy_five_move_draw());
+        assert!(!self::game(rooks, &["Rxa7"]).can_claim_fifty_moves());
+
+        let game = self::game("4k3/r7/8/8/8/8/R7/4K3 w - - 149 80", &["Rb2"]);
+        assert!(game.is_seventy_five_move_draw());
+        assert_eq!(
+            game.outcome(),
+            Some(GameOutcome::draw(Termination::SeventyFiveMoves))
+        );
+
+        // checkmate on the seventy-fifth move stands
+        let game = self::game("6k1/5ppp/8/8/8/8/8/R5K1 w - - 149 80", &["Ra8#"]);
+        assert!(!game.is_seventy_five_move_draw());
+        assert_eq!(game.outcome().unwrap().termination, Termination::Checkmate);
+    }
+
+    #[test]
+    fn insufficient_material() {
+        for (fen, insufficient) in [
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/4KN2 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1", true),
+            // same-colored bishops, on both sides
+            ("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1", true),
+            ("2b1k3/8/8/8/8/8/8/2B1KB2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/3NKN2 w - - 0 1", false),
+            ("4kn2/8/8/8/8/8/8/4KN2 w - - 0 1", false),
+            ("4kb2/8/8/8/8/8/8/4KN2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", false),
+        ] {
+            let game = game(fen, &[]);
+            assert_eq!(game.is_insufficient_material(), insufficient, "{fen}");
+            assert_eq!(game.outcome().is_some(), insufficient, "{fen}");
+        }
+    }
+}
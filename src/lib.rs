@@ -1,3 +1,9 @@
 //! A chess engine library.
 #![warn(missing_docs)]
-pub mod repr;
\ No newline at end of file
+pub mod attacks;
+pub mod game;
+pub mod movegen;
+pub mod perft;
+pub mod pgn;
+pub mod repr;
+pub mod tree;
@@ -0,0 +1,534 @@
+//! Move generation.
+//!
+//! Moves are generated into a [MoveList], which lives on the stack and can be reused between
+//! calls. Pseudo-legal generation may leave the mover's king in check; callers must filter
+//! those moves out themselves. Legal generation does that filtering up front, from the
+//! checkers and pinned pieces of the position, without playing any move.
+use std::fmt;
+use std::fmt::Formatter;
+use std::ops::Deref;
+
+use crate::attacks::{aligned, between, bishop_attacks, pawn_attacks, piece_attacks, rook_attacks};
+use crate::repr::board::masks::{RANK_2, RANK_3, RANK_6, RANK_7};
+use crate::repr::board::square::Square;
+use crate::repr::board::{BitBoard, Direction};
+use crate::repr::castling::CastlingSide;
+use crate::repr::moves::{Move, MoveFlag};
+use crate::repr::piece::{Color, Piece, PieceKind};
+use crate::repr::position::Position;
+
+/// The most moves a [MoveList] can hold.
+///
+/// No legal chess position has more than 218 moves, and no reachable one more than 256
+/// pseudo-legal moves.
+pub const MAX_MOVES: usize = 256;
+
+/// A fixed-capacity list of moves that lives on the stack.
+///
+/// A `MoveList` dereferences to a slice of the moves it holds.
+///
+/// # Examples
+///
+/// ```
+/// use chess::movegen::{generate_pseudo_legal, GenType, MoveList};
+/// use chess::repr::position::Position;
+/// let mut moves = MoveList::new();
+/// generate_pseudo_legal(&Position::startpos(), GenType::All, &mut moves);
+/// assert_eq!(moves.len(), 20);
+/// ```
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    /// An empty list.
+    pub const fn new() -> MoveList {
+        MoveList {
+            moves: [Move::NULL; MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    /// Appends a move.
+    ///
+    /// # Panics
+    ///
+    /// If the list already holds [MAX_MOVES] moves.
+    #[inline]
+    pub fn push(&mut self, mv: Move) {
+        assert!(self.len < MAX_MOVES, "more than {MAX_MOVES} moves");
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+
+    /// Removes every move.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Keeps only the moves for which `keep` returns `true`, preserving their order.
+    pub fn retain(&mut self, mut keep: impl FnMut(Move) -> bool) {
+        let mut len = 0;
+        for idx in 0..self.len {
+            let mv = self.moves[idx];
+            if keep(mv) {
+                self.moves[len] = mv;
+                len += 1;
+            }
+        }
+        self.len = len;
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        MoveList::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &Self::Target {
+        &self.moves[..self.len]
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for MoveList {
+    type Item = Move;
+    type IntoIter = std::iter::Take<std::array::IntoIter<Move, MAX_MOVES>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.moves.into_iter().take(self.len)
+    }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Which subset of the moves to generate.
+///
+/// Search examines captures before quiet moves, so the two can be generated separately;
+/// together they make up [GenType::All].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GenType {
+    /// Every move.
+    All,
+    /// Moves that capture a piece, including en passant and capturing promotions.
+    Captures,
+    /// Moves that capture nothing, including castling and non-capturing promotions.
+    Quiets,
+    /// Moves that may get the side to move out of check: king moves, captures of a lone
+    /// checker and interpositions. Equivalent to [GenType::All] when not in check.
+    Evasions,
+}
+
+/// Appends the pseudo-legal moves of `kind` for the side to move in `position` to `moves`.
+pub fn generate_pseudo_legal(position: &Position, kind: GenType, moves: &mut MoveList) {
+    let us = position.side_to_move();
+    let board = position.board();
+    let ours = board.color(us);
+    let theirs = board.color(us.opposite());
+    let occupied = board.occupied();
+
+    let kind = match kind {
+        GenType::Evasions if !position.in_check() => GenType::All,
+        kind => kind,
+    };
+    let checkers = position.checkers();
+    let king_targets = match kind {
+        GenType::All | GenType::Evasions => !ours,
+        GenType::Captures => theirs,
+        GenType::Quiets => !occupied,
+    };
+    let targets = match (kind, board.king(us)) {
+        (GenType::Evasions, _) if checkers.population_count() > 1 => BitBoard::EMPTY,
+        (GenType::Evasions, Some(king)) => {
+            let checker = checkers.lsb().expect("in check");
+            between(king, checker) | checkers
+        }
+        _ => king_targets,
+    };
+
+    generate_pawn_moves(position, kind, targets, moves);
+    for piece_kind in [
+        PieceKind::Knight,
+        PieceKind::Bishop,
+        PieceKind::Rook,
+        PieceKind::Queen,
+        PieceKind::King,
+    ] {
+        let piece = Piece::new(us, piece_kind);
+        let targets = if piece_kind == PieceKind::King {
+            king_targets
+        } else {
+            targets
+        };
+        for from in board.colored_pieces(us, piece_kind) {
+            for to in piece_attacks(piece, from, occupied) & targets {
+                let flag = if theirs.contains(to) {
+                    MoveFlag::Capture
+                } else {
+                    MoveFlag::Quiet
+                };
+                moves.push(Move::new(from, to, flag));
+            }
+        }
+    }
+    if matches!(kind, GenType::All | GenType::Quiets) {
+        generate_castling(position, moves);
+    }
+}
+
+/// Appends the legal moves for the side to move in `position` to `moves`.
+///
+/// Every appended move can be played without leaving the mover's king in check.
+pub fn generate_legal(position: &Position, moves: &mut MoveList) {
+    let start = moves.len();
+    generate_pseudo_legal(position, GenType::Evasions, moves);
+    let Some(king) = position.board().king(position.side_to_move()) else {
+        return;
+    };
+    let pinned = position.pinned();
+    let mut idx = 0;
+    moves.retain(|mv| {
+        idx += 1;
+        idx <= start || is_legal(position, king, pinned, mv)
+    });
+}
+
+/// Whether the pseudo-legal evasion or move `mv` leaves the king on `king` safe.
+///
+/// Evasion generation already restricts non-king moves to the check mask, so only king moves,
+/// pinned pieces and en passant need a closer look.
+fn is_legal(position: &Position, king: Square, pinned: BitBoard, mv: Move) -> bool {
+    let board = position.board();
+    let us = position.side_to_move();
+    let them = board.color(us.opposite());
+    let (from, to) = (mv.from(), mv.to());
+
+    if from == king {
+        // castling already checked that the king does not pass through check
+        let occupied = board.occupied() ^ BitBoard::from_square(king);
+        return mv.is_castle() || (position.attackers_to(to, occupied) & them).is_empty();
+    }
+    if mv.is_en_passant() {
+        // two pawns leave the rank at once, which can expose the king along it
+        let captured = Square::new(to.file(), from.rank());
+        let occupied =
+            (board.occupied() ^ BitBoard::from_square(from) ^ BitBoard::from_square(captured))
+                | BitBoard::from_square(to);
+        let rooks = board.pieces(PieceKind::Rook) | board.pieces(PieceKind::Queen);
+        let bishops = board.pieces(PieceKind::Bishop) | board.pieces(PieceKind::Queen);
+        return (rook_attacks(king, occupied) & rooks & them).is_empty()
+            && (bishop_attacks(king, occupied) & bishops & them).is_empty();
+    }
+    !pinned.contains(from) || aligned(king, from, to)
+}
+
+/// The square one step from `to` against `direction`.
+#[inline]
+fn behind(to: Square, direction: Direction) -> Square {
+    Square::ALL[(to as i8 - direction.index_delta()) as usize]
+}
+
+fn push_promotions(from: Square, to: Square, capture: bool, moves: &mut MoveList) {
+    for kind in [
+        PieceKind::Queen,
+        PieceKind::Knight,
+        PieceKind::Rook,
+        PieceKind::Bishop,
+    ] {
+        moves.push(Move::new(from, to, MoveFlag::promotion(kind, capture)));
+    }
+}
+
+fn generate_pawn_moves(
+    position: &Position,
+    kind: GenType,
+    targets: BitBoard,
+    moves: &mut MoveList,
+) {
+    let us = position.side_to_move();
+    let board = position.board();
+    let theirs = board.color(us.opposite());
+    let empty = !board.occupied();
+    let (up, up_east, up_west, last_rank, double_rank) = match us {
+        Color::White => (
+            Direction::North,
+            Direction::NorthEast,
+            Direction::NorthWest,
+            RANK_7,
+            RANK_3,
+        ),
+        Color::Black => (
+            Direction::South,
+            Direction::SouthEast,
+            Direction::SouthWest,
+            RANK_2,
+            RANK_6,
+        ),
+    };
+    let pawns = board.colored_pieces(us, PieceKind::Pawn);
+    let promoting = pawns & last_rank;
+    let pawns = pawns & !last_rank;
+
+    let single = pawns.shift(up) & empty;
+    let double = (single & double_rank).shift(up) & empty;
+    for to in single & targets {
+        moves.push(Move::new(behind(to, up), to, MoveFlag::Quiet));
+    }
+    for to in double & targets {
+        let from = behind(behind(to, up), up);
+        moves.push(Move::new(from, to, MoveFlag::DoublePawnPush));
+    }
+    for to in promoting.shift(up) & empty & targets {
+        push_promotions(behind(to, up), to, false, moves);
+    }
+
+    for direction in [up_east, up_west] {
+        for to in pawns.shift(direction) & theirs & targets {
+            moves.push(Move::new(behind(to, direction), to, MoveFlag::Capture));
+        }
+        for to in promoting.shift(direction) & theirs & targets {
+            push_promotions(behind(to, direction), to, true, moves);
+        }
+    }
+
+    if kind == GenType::Quiets {
+        return;
+    }
+    if let Some(ep) = position.en_passant() {
+        let captured = behind(ep, up);
+        if kind == GenType::Evasions && !(targets.contains(ep) || targets.contains(captured)) {
+            return;
+        }
+        for from in pawn_attacks(us.opposite(), ep) & pawns {
+            moves.push(Move::new(from, ep, MoveFlag::EnPassant));
+        }
+    }
+}
+
+fn generate_castling(position: &Position, moves: &mut MoveList) {
+    let us = position.side_to_move();
+    let board = position.board();
+    let Some(king) = board.king(us) else {
+        return;
+    };
+    if position.in_check() {
+        return;
+    }
+    let them = board.color(us.opposite());
+    for side in CastlingSide::ALL {
+        let Some(rook) = position.castling_rights().rook_square(us, side, king) else {
+            continue;
+        };
+        if king.rank() != rook.rank()
+            || board.piece_at(rook) != Some(Piece::new(us, PieceKind::Rook))
+            || !(side.empty_squares(king, rook) & board.occupied()).is_empty()
+        {
+            continue;
+        }
+        // the castling rook may have been shielding the king's destination in Chess960
+        let occupied = board.occupied() ^ BitBoard::from_square(king) ^ BitBoard::from_square(rook);
+        if side
+            .unattacked_squares(king)
+            .into_iter()
+            .any(|square| !(position.attackers_to(square, occupied) & them).is_empty())
+        {
+            continue;
+        }
+        let flag = match side {
+            CastlingSide::KingSide => MoveFlag::KingCastle,
+            CastlingSide::QueenSide => MoveFlag::QueenCastle,
+        };
+        moves.push(Move::new(king, side.move_destination(king, rook), flag));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(fen: &str, kind: GenType) -> MoveList {
+        let mut moves = MoveList::new();
+        generate_pseudo_legal(&fen.parse().unwrap(), kind, &mut moves);
+        moves
+    }
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    #[test]
+    #[should_panic(expected = "more than 256 moves")]
+    fn push_past_capacity_panics() {
+        let mut moves = MoveList::new();
+        for _ in 0..=MAX_MOVES {
+            moves.push(Move::NULL);
+        }
+    }
+
+    #[test]
+    fn kiwipete_move_counts() {
+        let all = generate(KIWIPETE, GenType::All);
+        assert_eq!(all.len(), 48);
+        assert_eq!(all.iter().filter(|mv| mv.is_capture()).count(), 8);
+        assert_eq!(all.iter().filter(|mv| mv.is_castle()).count(), 2);
+    }
+
+    #[test]
+    fn captures_and_quiets_partition_all() {
+        for fen in [
+            Position::STARTING_FEN,
+            KIWIPETE,
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ] {
+            let mut all: Vec<Move> = generate(fen, GenType::All).to_vec();
+            let captures = generate(fen, GenType::Captures);
+            let quiets = generate(fen, GenType::Quiets);
+            assert!(captures.iter().all(|mv| mv.is_capture()), "{fen}");
+            assert!(quiets.iter().all(|mv| !mv.is_capture()), "{fen}");
+            let mut staged: Vec<Move> = captures.iter().chain(&quiets).copied().collect();
+            all.sort_by_key(|mv| mv.bits());
+            staged.sort_by_key(|mv| mv.bits());
+            assert_eq!(all, staged, "{fen}");
+        }
+    }
+
+    fn legal(fen: &str) -> Vec<String> {
+        let position: Position = fen.parse().unwrap();
+        let mut uci: Vec<String> = position.legal_moves().iter().map(Move::to_string).collect();
+        uci.sort();
+        uci
+    }
+
+    #[test]
+    fn legal_move_counts_of_standard_positions() {
+        for (fen, count) in [
+            (Position::STARTING_FEN, 20),
+            (KIWIPETE, 48),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 14),
+            (
+                "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+                6,
+            ),
+            (
+                "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+                44,
+            ),
+            (
+                "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+                46,
+            ),
+        ] {
+            assert_eq!(legal(fen).len(), count, "{fen}");
+        }
+    }
+
+    #[test]
+    fn pinned_pieces_stay_on_the_pin_line() {
+        // the bishop on d2 is pinned by the bishop on a5 and may only capture it or slide
+        let moves = legal("7k/8/8/b7/8/8/3B4/4K3 w - - 0 1");
+        let bishop: Vec<&String> = moves.iter().filter(|uci| uci.starts_with("d2")).collect();
+        assert_eq!(bishop, ["d2a5", "d2b4", "d2c3"]);
+    }
+
+    #[test]
+    fn en_passant_cannot_expose_the_king_along_the_rank() {
+        let moves = legal("8/8/8/KPp4r/8/8/8/7k w - c6 0 1");
+        assert!(!moves.contains(&"b5c6".to_owned()));
+        let moves = legal("8/8/8/KPp5/8/8/8/7k w - c6 0 1");
+        assert!(moves.contains(&"b5c6".to_owned()));
+    }
+
+    #[test]
+    fn king_cannot_retreat_along_the_checking_ray() {
+        assert_eq!(
+            legal("4r2k/8/8/8/8/8/8/4K3 w - - 0 1"),
+            ["e1d1", "e1d2", "e1f1", "e1f2"]
+        );
+    }
+
+    #[test]
+    fn en_passant_and_promotions() {
+        let moves = generate(
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            GenType::Captures,
+        );
+        assert_eq!(moves.iter().filter(|mv| mv.is_en_passant()).count(), 1);
+        let moves = generate("1r5k/P7/8/8/8/8/8/K7 w - - 0 1", GenType::All);
+        assert_eq!(moves.iter().filter(|mv| mv.is_promotion()).count(), 8);
+    }
+
+    #[test]
+    fn evasions_block_or_capture_the_checker() {
+        // the rook on e8 checks the king on e1, and the bishop can only interpose on e3
+        let moves = generate("4r2k/8/8/8/8/8/3B4/4K3 w - - 0 1", GenType::Evasions);
+        let mut uci: Vec<String> = moves.iter().map(|mv| mv.to_string()).collect();
+        uci.sort();
+        assert_eq!(uci, ["d2e3", "e1d1", "e1e2", "e1f1", "e1f2"]);
+        // double check leaves only king moves
+        let moves = generate("4r2k/8/8/8/8/3n4/3B4/4K3 w - - 0 1", GenType::Evasions);
+        assert!(moves.iter().all(|mv| mv.from() == Square::E1));
+    }
+
+    #[test]
+    fn castling_through_check_is_not_generated() {
+        let moves = generate("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", GenType::Quiets);
+        assert_eq!(moves.iter().filter(|mv| mv.is_castle()).count(), 2);
+        let moves = generate("r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1", GenType::Quiets);
+        assert_eq!(moves.iter().filter(|mv| mv.is_castle()).count(), 1);
+        let moves = generate("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1", GenType::Quiets);
+        assert_eq!(moves.iter().filter(|mv| mv.is_castle()).count(), 0);
+    }
+
+    #[test]
+    fn chess960_castling_is_the_king_taking_its_rook() {
+        let position: Position = "7k/8/8/8/8/8/8/RK6 w A - 0 1".parse().unwrap();
+        let b1c1 = position
+            .legal_moves()
+            .iter()
+            .filter(|mv| mv.to_string() == "b1c1")
+            .count();
+        assert_eq!(b1c1, 1);
+        let castle = position
+            .legal_moves()
+            .iter()
+            .copied()
+            .find(|mv| mv.is_castle());
+        assert_eq!(castle.map(|mv| mv.to_string()).as_deref(), Some("b1a1"));
+        // the king already on its destination still names a move
+        let position: Position = "7k/8/8/8/8/8/8/5RK1 w F - 0 1".parse().unwrap();
+        let castle = position
+            .legal_moves()
+            .iter()
+            .copied()
+            .find(|mv| mv.is_castle());
+        assert_eq!(castle.map(|mv| mv.to_string()).as_deref(), Some("g1f1"));
+        // standard castling stays the king's move
+        let moves = generate("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", GenType::Quiets);
+        let mut castles: Vec<String> = moves
+            .iter()
+            .filter(|mv| mv.is_castle())
+            .map(|mv| mv.to_string())
+            .collect();
+        castles.sort();
+        assert_eq!(castles, ["e1c1", "e1g1"]);
+    }
+}
@@ -0,0 +1,78 @@
+//! Move path enumeration, for validating move generation.
+//!
+//! [Perft] counts the leaf nodes of the legal move tree to a fixed depth. The counts of many
+//! positions are well known, so any difference points to a move generation bug; [divide]
+//! narrows it down to the first move whose subtree differs.
+//!
+//! [Perft]: https://www.chessprogramming.org/Perft
+use crate::movegen::{generate_legal, MoveList};
+use crate::repr::moves::Move;
+use crate::repr::position::Position;
+
+/// The number of leaf nodes of the legal move tree of `position`, `depth` plies deep.
+///
+/// Moves at the last ply are counted rather than played.
+///
+/// # Examples
+///
+/// ```
+/// use chess::perft::perft;
+/// use chess::repr::position::Position;
+/// assert_eq!(perft(&mut Position::startpos(), 3), 8_902);
+/// ```
+pub fn perft(position: &mut Position, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let mut moves = MoveList::new();
+    generate_legal(position, &mut moves);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    let mut nodes = 0;
+    for &mv in &moves {
+        let undo = position.make_move(mv);
+        nodes += perft(position, depth - 1);
+        position.unmake_move(mv, undo);
+    }
+    nodes
+}
+
+/// The [perft] count below each legal move of `position`, in generation order.
+///
+/// The counts sum to `perft(position, depth)`. At depth 0 there are no moves to divide by.
+pub fn divide(position: &mut Position, depth: u32) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    position
+        .legal_moves()
+        .into_iter()
+        .map(|mv| {
+            let undo = position.make_move(mv);
+            let nodes = perft(position, depth - 1);
+            position.unmake_move(mv, undo);
+            (mv, nodes)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn divide_sums_to_perft() {
+        let mut position = Position::startpos();
+        let divided = divide(&mut position, 3);
+        assert_eq!(divided.len(), 20);
+        assert_eq!(divided.iter().map(|(_, n)| n).sum::<u64>(), 8_902);
+        assert_eq!(position, Position::startpos());
+    }
+
+    #[test]
+    fn depth_zero_is_one_node() {
+        assert_eq!(perft(&mut Position::startpos(), 0), 1);
+        assert!(divide(&mut Position::startpos(), 0).is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+//! Reading and writing games in Portable Game Notation.
+//!
+//! [PGN] is the common exchange format for chess games: a section of tag pairs such as
+//! `[White "Kasparov"]`, followed by the moves in SAN, interleaved with comments, numeric
+//! annotation glyphs (NAGs) and variations, and ended by the result.
+//!
+//! [PgnReader] streams [Game]s from any [BufRead](std::io::BufRead) one at a time, so files
+//! of any size can be processed in constant memory. [PgnWriter] writes them back.
+//!
+//! [PGN]: https://www.chessprogramming.org/Portable_Game_Notation
+mod lexer;
+mod reader;
+mod writer;
+
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use crate::repr::moves::Move;
+use crate::repr::position::{FenError, Position};
+
+pub use reader::{PgnError, PgnReader, MAX_VARIATION_DEPTH};
+pub use writer::PgnWriter;
+
+/// The result of a game, as written at the end of its movetext and in its `Result` tag.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub enum GameResult {
+    /// White won, `1-0`.
+    WhiteWins,
+    /// Black won, `0-1`.
+    BlackWins,
+    /// The game was drawn, `1/2-1/2`.
+    Draw,
+    /// The game is unfinished or its result is unknown, `*`.
+    #[default]
+    Unknown,
+}
+
+impl FromStr for GameResult {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1-0" => Ok(GameResult::WhiteWins),
+            "0-1" => Ok(GameResult::BlackWins),
+            "1/2-1/2" => Ok(GameResult::Draw),
+            "*" => Ok(GameResult::Unknown),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Unknown => "*",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// A move of a game, with the annotations that follow it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveNode {
+    /// The move, legal in the position it was played from.
+    pub mv: Move,
+    /// The numeric annotation glyphs of the move, e.g. `1` for `$1` or `!`.
+    pub nags: Vec<u8>,
+    /// The comments following the move, without their braces.
+    pub comments: Vec<String>,
+    /// Alternatives to this move, each played from the same position.
+    pub variations: Vec<Variation>,
+}
+
+impl MoveNode {
+    /// A node for `mv`, without annotations.
+    pub fn new(mv: Move) -> MoveNode {
+        MoveNode {
+            mv,
+            nags: Vec::new(),
+            comments: Vec::new(),
+            variations: Vec::new(),
+        }
+    }
+}
+
+/// A sequence of moves, with the comments that precede the first.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Variation {
+    /// The comments before the first move.
+    pub comments: Vec<String>,
+    /// The moves, each played from the position the previous one led to.
+    pub moves: Vec<MoveNode>,
+}
+
+/// A game read from PGN.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Game {
+    /// The tag pairs, in the order they were read.
+    pub tags: Vec<(String, String)>,
+    /// The main line.
+    pub mainline: Variation,
+    /// The result ending the movetext.
+    pub result: GameResult,
+}
+
+impl Game {
+    /// A game without moves from `position`.
+    ///
+    /// Unless `position` is the standard starting position, the `SetUp` and `FEN` tags are set
+    /// to it.
+    pub fn from_position(position: &Position) -> Game {
+        let mut game = Game::default();
+        if *position != Position::startpos() {
+            game.tags.push(("SetUp".to_owned(), "1".to_owned()));
+            game.tags.push(("FEN".to_owned(), position.to_string()));
+        }
+        game
+    }
+
+    /// The value of the first tag named `name`, if any.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The position the game starts from: that of the `FEN` tag, or the standard starting
+    /// position if there is none.
+    pub fn starting_position(&self) -> Result<Position, FenError> {
+        match self.tag("FEN") {
+            Some(fen) => fen.parse(),
+            None => Ok(Position::startpos()),
+        }
+    }
+}
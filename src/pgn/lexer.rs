@@ -0,0 +1,264 @@
+//! Splitting PGN into tokens, a line at a time.
+use std::io;
+use std::io::BufRead;
+
+use super::reader::PgnError;
+
+/// A lexical unit of PGN.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum Token {
+    /// `[`, opening a tag pair.
+    TagOpen,
+    /// `]`, closing a tag pair.
+    TagClose,
+    /// A quoted string, unescaped.
+    String(String),
+    /// A tag name, move, move number or result, or `--` for the null move.
+    Symbol(String),
+    /// `.`, following a move number.
+    Period,
+    /// `*`, the unknown result.
+    Asterisk,
+    /// A numeric annotation glyph, from `$n` or a suffix such as `!?`.
+    Nag(u8),
+    /// A brace or rest-of-line comment, without its delimiters.
+    Comment(String),
+    /// `(`, opening a variation.
+    VariationOpen,
+    /// `)`, closing a variation.
+    VariationClose,
+}
+
+/// A token and the line and column, both starting at 1, it starts on.
+pub(super) type Spanned = (Token, usize, usize);
+
+/// The suffix annotations of import format and the NAGs they stand for.
+const SUFFIXES: [(&str, u8); 6] = [
+    ("!", 1),
+    ("?", 2),
+    ("!!", 3),
+    ("??", 4),
+    ("!?", 5),
+    ("?!", 6),
+];
+
+/// Reads [Token]s from a [BufRead], holding only the current line in memory.
+pub(super) struct Lexer<R> {
+    reader: R,
+    bytes: Vec<u8>,
+    line: Vec<char>,
+    pos: usize,
+    line_number: usize,
+    peeked: Option<Spanned>,
+}
+
+impl<R: BufRead> Lexer<R> {
+    pub(super) fn new(reader: R) -> Lexer<R> {
+        Lexer {
+            reader,
+            bytes: Vec::new(),
+            line: Vec::new(),
+            pos: 0,
+            line_number: 0,
+            peeked: None,
+        }
+    }
+
+    /// The line and column of the next character.
+    pub(super) fn location(&self) -> (usize, usize) {
+        (self.line_number, self.pos + 1)
+    }
+
+    /// Reads the next line, returning `false` at the end of the input.
+    ///
+    /// Bytes that are not UTF-8, as in the many PGN files encoded in Latin-1, are replaced
+    /// rather than rejected.
+    fn next_line(&mut self) -> io::Result<bool> {
+        self.bytes.clear();
+        if self.reader.read_until(b'\n', &mut self.bytes)? == 0 {
+            self.line.clear();
+            self.pos = 0;
+            return Ok(false);
+        }
+        self.line = String::from_utf8_lossy(&self.bytes).chars().collect();
+        if self.line_number == 0 && self.line.first() == Some(&'\u{feff}') {
+            self.line.remove(0);
+        }
+        self.pos = 0;
+        self.line_number += 1;
+        Ok(true)
+    }
+
+    /// The next character, reading further lines as needed.
+    fn peek_char(&mut self) -> io::Result<Option<char>> {
+        while self.pos >= self.line.len() {
+            if !self.next_line()? {
+                return Ok(None);
+            }
+            if self.line.first() == Some(&'%') {
+                // escape mechanism: the whole line is ignored
+                self.pos = self.line.len();
+            }
+        }
+        Ok(Some(self.line[self.pos]))
+    }
+
+    fn next_char(&mut self) -> io::Result<Option<char>> {
+        let c = self.peek_char()?;
+        self.pos += 1;
+        Ok(c)
+    }
+
+    /// The next token without consuming it.
+    pub(super) fn peek(&mut self) -> Result<Option<&Spanned>, PgnError> {
+        if self.peeked.is_none() {
+            self.peeked = self.read_token()?;
+        }
+        Ok(self.peeked.as_ref())
+    }
+
+    /// The next token.
+    pub(super) fn next_token(&mut self) -> Result<Option<Spanned>, PgnError> {
+        match self.peeked.take() {
+            Some(token) => Ok(Some(token)),
+            None => self.read_token(),
+        }
+    }
+
+    /// Skips ahead to the next line starting with `[`, which should open the tag pairs of the
+    /// next game.
+    pub(super) fn skip_to_next_game(&mut self) -> Result<(), PgnError> {
+        if let Some((Token::TagOpen, _, 1)) = self.peeked.take() {
+            self.pos = 0;
+        }
+        loop {
+            if self.pos == 0 && self.line.first() == Some(&'[') {
+                return Ok(());
+            }
+            if !self.next_line()? {
+                return Ok(());
+            }
+        }
+    }
+
+    fn read_token(&mut self) -> Result<Option<Spanned>, PgnError> {
+        while let Some(c) = self.peek_char()? {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.pos += 1;
+        }
+        let (line, column) = self.location();
+        let Some(c) = self.next_char()? else {
+            return Ok(None);
+        };
+        let token = match c {
+            '[' => Token::TagOpen,
+            ']' => Token::TagClose,
+            '(' => Token::VariationOpen,
+            ')' => Token::VariationClose,
+            '.' => Token::Period,
+            '*' => Token::Asterisk,
+            '"' => Token::String(self.read_string(line, column)?),
+            '{' => Token::Comment(self.read_comment(line, column)?),
+            ';' => {
+                let comment: String = self.line[self.pos..].iter().collect();
+                self.pos = self.line.len();
+                Token::Comment(comment.trim_end_matches(['\r', '\n']).to_owned())
+            }
+            '$' => {
+                let digits = self.take_while(|c| c.is_ascii_digit());
+                Token::Nag(digits.parse().map_err(|_| PgnError::Unexpected {
+                    line,
+                    column,
+                    found: format!("${digits}"),
+                })?)
+            }
+            '!' | '?' => {
+                let glyph = format!("{c}{}", self.take_while(|c| c == '!' || c == '?'));
+                let nag = SUFFIXES
+                    .iter()
+                    .find(|(suffix, _)| *suffix == glyph)
+                    .map(|&(_, nag)| nag)
+                    .ok_or(PgnError::Unexpected {
+                        line,
+                        column,
+                        found: glyph,
+                    })?;
+                Token::Nag(nag)
+            }
+            '-' if self.line.get(self.pos) == Some(&'-') => {
+                // the null move
+                self.pos += 1;
+                Token::Symbol("--".to_owned())
+            }
+            c if c.is_ascii_alphanumeric() => {
+                let rest = self.take_while(|c| c.is_ascii_alphanumeric() || "_+#=:-/".contains(c));
+                Token::Symbol(format!("{c}{rest}"))
+            }
+            c => {
+                return Err(PgnError::Unexpected {
+                    line,
+                    column,
+                    found: c.to_string(),
+                })
+            }
+        };
+        Ok(Some((token, line, column)))
+    }
+
+    /// The characters of the current line from the cursor for as long as `accept` holds.
+    fn take_while(&mut self, accept: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        while self.pos < self.line.len() && accept(self.line[self.pos]) {
+            self.pos += 1;
+        }
+        self.line[start..self.pos].iter().collect()
+    }
+
+    fn read_string(&mut self, line: usize, column: usize) -> Result<String, PgnError> {
+        let mut string = String::new();
+        loop {
+            // strings may not span lines
+            let c = match self.line.get(self.pos) {
+                Some('\n' | '\r') | None => {
+                    return Err(PgnError::Unterminated {
+                        line,
+                        column,
+                        what: "string",
+                    })
+                }
+                Some(&c) => c,
+            };
+            self.pos += 1;
+            match c {
+                '"' => return Ok(string),
+                '\\' => match self.line.get(self.pos) {
+                    Some(&escaped @ ('"' | '\\')) => {
+                        string.push(escaped);
+                        self.pos += 1;
+                    }
+                    _ => string.push('\\'),
+                },
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn read_comment(&mut self, line: usize, column: usize) -> Result<String, PgnError> {
+        let mut comment = String::new();
+        loop {
+            match self.next_char()? {
+                Some('}') => return Ok(comment.replace("\r\n", "\n")),
+                Some(c) => comment.push(c),
+                None => {
+                    return Err(PgnError::Unterminated {
+                        line,
+                        column,
+                        what: "comment",
+                    })
+                }
+            }
+        }
+    }
+}
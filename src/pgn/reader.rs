@@ -0,0 +1,536 @@
+//! Parsing [Game]s from a stream of PGN.
+use std::io;
+use std::io::BufRead;
+
+use thiserror::Error;
+
+use super::lexer::{Lexer, Spanned, Token};
+use super::{Game, GameResult, MoveNode, Variation};
+use crate::repr::position::{FenError, Position, SanError};
+
+/// How deep variations may nest: each level is read recursively, so deeper input could
+/// exhaust the stack.
+pub const MAX_VARIATION_DEPTH: usize = 64;
+
+/// An error raised when reading PGN.
+///
+/// Lines and columns start at 1 and count characters.
+#[derive(Debug, Error)]
+pub enum PgnError {
+    /// The underlying reader failed.
+    #[error("I/O error")]
+    Io(#[from] io::Error),
+    /// A token that does not belong where it was found.
+    #[error("{line}:{column}: unexpected {found:?}")]
+    Unexpected {
+        /// The line of the token.
+        line: usize,
+        /// The column of the token.
+        column: usize,
+        /// The offending text.
+        found: String,
+    },
+    /// A string, comment, tag pair or variation that is never closed.
+    #[error("{line}:{column}: unterminated {what}")]
+    Unterminated {
+        /// The line the construct starts on.
+        line: usize,
+        /// The column the construct starts on.
+        column: usize,
+        /// What is left open.
+        what: &'static str,
+    },
+    /// The `FEN` tag does not hold a valid position.
+    #[error("{line}:{column}: invalid FEN tag")]
+    InvalidFen {
+        /// The line of the tag.
+        line: usize,
+        /// The column of the tag.
+        column: usize,
+        /// The reason the FEN could not be parsed.
+        #[source]
+        source: FenError,
+    },
+    /// Variations nested deeper than a reader follows.
+    #[error("{line}:{column}: variations nested deeper than {MAX_VARIATION_DEPTH}")]
+    TooDeep {
+        /// The line of the variation.
+        line: usize,
+        /// The column of the variation.
+        column: usize,
+    },
+    /// A move that is not legal SAN in its position.
+    #[error("{line}:{column}: invalid move {san:?}")]
+    InvalidMove {
+        /// The line of the move.
+        line: usize,
+        /// The column of the move.
+        column: usize,
+        /// The move as written.
+        san: String,
+        /// The reason the move was rejected.
+        #[source]
+        source: SanError,
+    },
+}
+
+impl PgnError {
+    /// The line and column the error was found at, unless it is an I/O error.
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match *self {
+            PgnError::Io(_) => None,
+            PgnError::Unexpected { line, column, .. }
+            | PgnError::Unterminated { line, column, .. }
+            | PgnError::InvalidFen { line, column, .. }
+            | PgnError::TooDeep { line, column }
+            | PgnError::InvalidMove { line, column, .. } => Some((line, column)),
+        }
+    }
+}
+
+/// Reads [Game]s one at a time from a [BufRead].
+///
+/// Only the current line and game are held in memory. Every move is checked to be legal.
+///
+/// By default, reading stops at the first error. With [`PgnReader::skip_malformed`], games
+/// that fail to parse are skipped instead, and reading resumes at the next line starting
+/// with `[`.
+///
+/// # Examples
+///
+/// ```
+/// use chess::pgn::{GameResult, PgnReader};
+/// let pgn = r#"[Event "Casual game"]
+///
+/// 1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6?? 4. Qxf7# 1-0
+/// "#;
+/// let games: Vec<_> = PgnReader::new(pgn.as_bytes()).collect::<Result<_, _>>().unwrap();
+/// assert_eq!(games.len(), 1);
+/// assert_eq!(games[0].tag("Event"), Some("Casual game"));
+/// assert_eq!(games[0].mainline.moves.len(), 7);
+/// assert_eq!(games[0].result, GameResult::WhiteWins);
+/// ```
+pub struct PgnReader<R> {
+    lexer: Lexer<R>,
+    skip_malformed: bool,
+    skipped: usize,
+    done: bool,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    /// A reader of the games in `reader`.
+    pub fn new(reader: R) -> PgnReader<R> {
+        PgnReader {
+            lexer: Lexer::new(reader),
+            skip_malformed: false,
+            skipped: 0,
+            done: false,
+        }
+    }
+
+    /// Sets whether games that fail to parse are skipped rather than ending the iteration.
+    ///
+    /// I/O errors always end the iteration.
+    pub fn skip_malformed(mut self, skip: bool) -> PgnReader<R> {
+        self.skip_malformed = skip;
+        self
+    }
+
+    /// The number of malformed games skipped so far.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Reads the next game, or `None` at the end of the input.
+    pub fn read_game(&mut self) -> Result<Option<Game>, PgnError> {
+        if self.lexer.peek()?.is_none() {
+            return Ok(None);
+        }
+
+        let mut game = Game::default();
+        let mut fen_location = (0, 0);
+        while let Some((Token::TagOpen, line, column)) = self.lexer.peek()?.cloned() {
+            self.lexer.next_token()?;
+            let name = match self.tag_token()? {
+                Some((Token::Symbol(name), ..)) => name,
+                other => return Err(unexpected(other, line, column, "tag pair")),
+            };
+            let value = match self.tag_token()? {
+                Some((Token::String(value), ..)) => value,
+                other => return Err(unexpected(other, line, column, "tag pair")),
+            };
+            match self.tag_token()? {
+                Some((Token::TagClose, ..)) => {}
+                other => return Err(unexpected(other, line, column, "tag pair")),
+            }
+            if name == "FEN" {
+                fen_location = (line, column);
+            }
+            game.tags.push((name, value));
+        }
+
+        let position = game
+            .starting_position()
+            .map_err(|source| PgnError::InvalidFen {
+                line: fen_location.0,
+                column: fen_location.1,
+                source,
+            })?;
+        let (mainline, result) = self.read_variation(position, None, 0)?;
+        game.mainline = mainline;
+        game.result = result
+            .or_else(|| game.tag("Result").and_then(|result| result.parse().ok()))
+            .unwrap_or_default();
+        Ok(Some(game))
+    }
+
+    /// The next token of a tag pair.
+    ///
+    /// A `[` is left unread, so that skipping a malformed game resumes at the tag pair it
+    /// opens.
+    fn tag_token(&mut self) -> Result<Option<Spanned>, PgnError> {
+        match self.lexer.peek()? {
+            Some(spanned @ (Token::TagOpen, ..)) => Ok(Some(spanned.clone())),
+            _ => self.lexer.next_token(),
+        }
+    }
+
+    /// Reads moves played from `position` up to the end of a variation or the game.
+    ///
+    /// `opened_at` is the location of the `(` of a variation, or `None` for the main line;
+    /// only the main line may end with a result. `depth` counts the variations enclosing
+    /// this one.
+    fn read_variation(
+        &mut self,
+        mut position: Position,
+        opened_at: Option<(usize, usize)>,
+        depth: usize,
+    ) -> Result<(Variation, Option<GameResult>), PgnError> {
+        let mut variation = Variation::default();
+        // the position before the last move, from which its variations are played
+        let mut previous = position;
+        loop {
+            let Some((token, line, column)) = self.lexer.peek()?.cloned() else {
+                return match opened_at {
+                    Some((line, column)) => Err(PgnError::Unterminated {
+                        line,
+                        column,
+                        what: "variation",
+                    }),
+                    None => Ok((variation, None)),
+                };
+            };
+            if token == Token::TagOpen {
+                // the next game, after a game that lacks its result; left unread so that
+                // skipping a malformed game resumes there
+                return match opened_at {
+                    Some((line, column)) => Err(PgnError::Unterminated {
+                        line,
+                        column,
+                        what: "variation",
+                    }),
+                    None => Ok((variation, None)),
+                };
+            }
+            self.lexer.next_token()?;
+            match token {
+                Token::Comment(comment) => match variation.moves.last_mut() {
+                    Some(node) => node.comments.push(comment),
+                    None => variation.comments.push(comment),
+                },
+                Token::Nag(nag) => match variation.moves.last_mut() {
+                    Some(node) => node.nags.push(nag),
+                    None => return Err(unexpected_token(&token, line, column)),
+                },
+                Token::Period => {}
+                Token::VariationOpen => {
+                    if variation.moves.is_empty() {
+                        return Err(unexpected_token(&token, line, column));
+                    }
+                    if depth == MAX_VARIATION_DEPTH {
+                        return Err(PgnError::TooDeep { line, column });
+                    }
+                    let (alternative, _) =
+                        self.read_variation(previous, Some((line, column)), depth + 1)?;
+                    if let Some(node) = variation.moves.last_mut() {
+                        node.variations.push(alternative);
+                    }
+                }
+                Token::VariationClose if opened_at.is_some() => return Ok((variation, None)),
+                Token::Asterisk if opened_at.is_none() => {
+                    return Ok((variation, Some(GameResult::Unknown)))
+                }
+                Token::Symbol(symbol) => {
+                    if symbol.bytes().all(|b| b.is_ascii_digit()) {
+                        continue;
+                    }
+                    if let Ok(result) = symbol.parse::<GameResult>() {
+                        if opened_at.is_none() {
+                            return Ok((variation, Some(result)));
+                        }
+                        return Err(PgnError::Unexpected {
+                            line,
+                            column,
+                            found: symbol,
+                        });
+                    }
+                    let mv =
+                        position
+                            .parse_san(&symbol)
+                            .map_err(|source| PgnError::InvalidMove {
+                                line,
+                                column,
+                                san: symbol,
+                                source,
+                            })?;
+                    previous = position;
+                    position.make_move(mv);
+                    variation.moves.push(MoveNode::new(mv));
+                }
+                token => return Err(unexpected_token(&token, line, column)),
+            }
+        }
+    }
+}
+
+/// The error for finding `found` where it does not belong.
+///
+/// At the end of the input, the construct opened at `line` and `column` is unterminated.
+fn unexpected(found: Option<Spanned>, line: usize, column: usize, what: &'static str) -> PgnError {
+    match found {
+        Some((token, line, column)) => unexpected_token(&token, line, column),
+        None => PgnError::Unterminated { line, column, what },
+    }
+}
+
+/// The error for finding `token` at `line` and `column`, where it does not belong.
+fn unexpected_token(token: &Token, line: usize, column: usize) -> PgnError {
+    PgnError::Unexpected {
+        line,
+        column,
+        found: describe(token),
+    }
+}
+
+/// The PGN text of `token`.
+fn describe(token: &Token) -> String {
+    match token {
+        Token::TagOpen => "[".to_owned(),
+        Token::TagClose => "]".to_owned(),
+        Token::String(string) => format!("\"{string}\""),
+        Token::Symbol(symbol) => symbol.clone(),
+        Token::Period => ".".to_owned(),
+        Token::Asterisk => "*".to_owned(),
+        Token::Nag(nag) => format!("${nag}"),
+        Token::Comment(comment) => format!("{{{comment}}}"),
+        Token::VariationOpen => "(".to_owned(),
+        Token::VariationClose => ")".to_owned(),
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<Game, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            match self.read_game() {
+                Ok(Some(game)) => return Some(Ok(game)),
+                Ok(None) => self.done = true,
+                Err(PgnError::Io(err)) => {
+                    self.done = true;
+                    return Some(Err(PgnError::Io(err)));
+                }
+                Err(err) => {
+                    if !self.skip_malformed {
+                        self.done = true;
+                        return Some(Err(err));
+                    }
+                    self.skipped += 1;
+                    if let Err(err) = self.lexer.skip_to_next_game() {
+                        self.done = true;
+                        return Some(Err(err));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(pgn: &str) -> Vec<Result<Game, PgnError>> {
+        PgnReader::new(pgn.as_bytes()).collect()
+    }
+
+    fn sans(position: Position, variation: &Variation) -> Vec<String> {
+        let mut position = position;
+        variation
+            .moves
+            .iter()
+            .map(|node| {
+                let san = position.san(node.mv);
+                position.make_move(node.mv);
+                san
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reads_annotations_and_variations() {
+        let pgn = r#"[Event "Test"]
+[White "A \"quoted\" name"]
+[Result "1-0"]
+
+{Opening comment} 1. e4 $1 {Best by test} e5 (1... c5 {Sicilian} 2. Nf3 (2. c3) d6)
+2. Nf3!? ; rest of line
+Nc6 3. Bb5 a6 1-0
+"#;
+        let games = read(pgn);
+        assert_eq!(games.len(), 1);
+        let game = games[0].as_ref().unwrap();
+        assert_eq!(game.tag("White"), Some("A \"quoted\" name"));
+        assert_eq!(game.result, GameResult::WhiteWins);
+        assert_eq!(game.mainline.comments, ["Opening comment"]);
+
+        let start = Position::startpos();
+        assert_eq!(
+            sans(start, &game.mainline),
+            ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]
+        );
+        let e4 = &game.mainline.moves[0];
+        assert_eq!(e4.nags, [1]);
+        assert_eq!(e4.comments, ["Best by test"]);
+
+        let e5 = &game.mainline.moves[1];
+        assert_eq!(e5.variations.len(), 1);
+        let sicilian = &e5.variations[0];
+        let after_e4 = start.play(e4.mv);
+        assert_eq!(sans(after_e4, sicilian), ["c5", "Nf3", "d6"]);
+        assert_eq!(sicilian.moves[0].comments, ["Sicilian"]);
+        let after_c5 = after_e4.play(sicilian.moves[0].mv);
+        assert_eq!(sans(after_c5, &sicilian.moves[1].variations[0]), ["c3"]);
+
+        let nf3 = &game.mainline.moves[2];
+        assert_eq!(nf3.nags, [5]);
+        assert_eq!(nf3.comments, [" rest of line"]);
+    }
+
+    #[test]
+    fn streams_several_games() {
+        let pgn = "[Event \"1\"]\n\n1. d4 d5 1/2-1/2\n\n[Event \"2\"]\n\n1. c4 *\n\n\
+                   [Event \"3\"]\n[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/4K2R w K - 0 1\"]\n\n\
+                   1. O-O Kd7 0-1\n";
+        let games: Vec<Game> = read(pgn).into_iter().map(Result::unwrap).collect();
+        assert_eq!(games.len(), 3);
+        assert_eq!(games[0].result, GameResult::Draw);
+        assert_eq!(games[1].result, GameResult::Unknown);
+        assert_eq!(games[2].result, GameResult::BlackWins);
+        assert_eq!(games[2].mainline.moves[0].mv.to_string(), "e1g1");
+    }
+
+    #[test]
+    fn games_without_termination_take_the_result_tag() {
+        let pgn = "[Result \"0-1\"]\n1. f3 e5 2. g4 Qh4#\n\n[Result \"*\"]\n1. e4\n";
+        let games: Vec<Game> = read(pgn).into_iter().map(Result::unwrap).collect();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].result, GameResult::BlackWins);
+        assert_eq!(games[0].mainline.moves.len(), 4);
+        assert_eq!(games[1].mainline.moves.len(), 1);
+    }
+
+    #[test]
+    fn errors_report_line_and_column() {
+        let location = |pgn: &str| {
+            read(pgn)
+                .into_iter()
+                .find_map(Result::err)
+                .and_then(|err| err.location())
+        };
+        assert_eq!(location("[Event \"x\"]\n\n1. e4 e4 *\n"), Some((3, 7)));
+        assert_eq!(location("1. e4 {never closed\n"), Some((1, 7)));
+        assert_eq!(location("1. e4 (1. d4\n"), Some((1, 7)));
+        assert_eq!(location("[Event \"x]\n"), Some((1, 8)));
+        assert_eq!(location("1. e4 ) *"), Some((1, 7)));
+        assert_eq!(location("[FEN \"8/8\"]\n*"), Some((1, 1)));
+        assert!(matches!(
+            read("1. e4 e4 *").remove(0),
+            Err(PgnError::InvalidMove {
+                source: SanError::Illegal(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn stops_at_the_first_error_by_default() {
+        let pgn = "[Event \"1\"]\n1. e4 Ke7?? 2. Ke3 *\n\n[Event \"2\"]\n1. e4 *\n";
+        let results = read(pgn);
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
+
+    #[test]
+    fn recovery_skips_malformed_games() {
+        let pgn = "[Event \"1\"]\n1. e4 Ke7?? 2. Ke3 *\n\n[Event \"2\"]\n1. e4 (1. d4 *\n\n\
+                   [Event \"3\"]\n1. e4 e5 *\n";
+        let mut reader = PgnReader::new(pgn.as_bytes()).skip_malformed(true);
+        let games: Vec<Game> = reader.by_ref().map(Result::unwrap).collect();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].tag("Event"), Some("3"));
+        assert_eq!(reader.skipped(), 2);
+    }
+
+    #[test]
+    fn recovery_keeps_the_tags_of_the_next_game() {
+        for pgn in [
+            "[Event \"1\"]\n1. e4 (1. d4\n\n[Event \"2\"]\n[Site \"S\"]\n1. e4 *",
+            "[Event \"1\"\n[Event \"2\"]\n[Site \"S\"]\n1. e4 *",
+        ] {
+            let mut reader = PgnReader::new(pgn.as_bytes()).skip_malformed(true);
+            let games: Vec<Game> = reader.by_ref().map(Result::unwrap).collect();
+            assert_eq!(games.len(), 1, "{pgn:?}");
+            assert_eq!(
+                games[0].tags,
+                [
+                    ("Event".to_owned(), "2".to_owned()),
+                    ("Site".to_owned(), "S".to_owned())
+                ]
+            );
+            assert_eq!(reader.skipped(), 1);
+        }
+    }
+
+    #[test]
+    fn limits_variation_depth() {
+        let nested = |depth: usize| {
+            let mut pgn = "1. e4 ".to_owned();
+            pgn.push_str(&"(1. d4 ".repeat(depth));
+            pgn.push_str(&")".repeat(depth));
+            pgn.push_str(" *\n");
+            pgn
+        };
+        assert!(read(&nested(MAX_VARIATION_DEPTH)).remove(0).is_ok());
+        assert!(matches!(
+            read(&nested(MAX_VARIATION_DEPTH + 1)).remove(0),
+            Err(PgnError::TooDeep { line: 1, .. })
+        ));
+        // far deeper than the stack would allow
+        let pgn = format!("1. e4 {}", "(1. d4 ".repeat(100_000));
+        assert!(matches!(
+            read(&pgn).remove(0),
+            Err(PgnError::TooDeep { .. })
+        ));
+    }
+
+    #[test]
+    fn tolerates_latin1_and_escapes() {
+        let mut pgn = b"[White \"M\xfcller\"]\n% an escaped line\n1. e4 *\n".to_vec();
+        pgn.splice(0..0, "\u{feff}".bytes());
+        let game = PgnReader::new(&pgn[..]).next().unwrap().unwrap();
+        assert_eq!(game.tag("White"), Some("M\u{fffd}ller"));
+        assert_eq!(game.mainline.moves.len(), 1);
+    }
+}
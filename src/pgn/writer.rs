@@ -0,0 +1,440 @@
+//! Writing [Game]s as PGN.
+use std::io;
+use std::io::Write;
+
+use super::{Game, MoveNode, Variation};
+use crate::repr::piece::Color;
+use crate::repr::position::Position;
+
+/// The tags every PGN game has, in the order they are written, with their values when
+/// unknown.
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+/// The longest line of movetext written, as recommended by the PGN standard.
+const LINE_WIDTH: usize = 80;
+
+/// Writes [Game]s as PGN to a [Write].
+///
+/// The tag pairs start with the Seven Tag Roster, filled in with `?` where a game lacks
+/// them, followed by `SetUp` and `FEN` for games from a custom position and then the
+/// remaining tags in their original order. Movetext is wrapped at 80 columns.
+///
+/// Comments, variations, NAGs and `[%clk]` clock annotations inside comments are all written
+/// by default; each can be left out. Anything [PgnReader](super::PgnReader) reads is written
+/// back so that it reads the same.
+///
+/// # Examples
+///
+/// ```
+/// use chess::pgn::{PgnReader, PgnWriter};
+/// let pgn = "[White \"Morphy\"]\n\n1. e4 {Best by test} e5 (1... c5) 2. Nf3 *\n";
+/// let game = PgnReader::new(pgn.as_bytes()).next().unwrap().unwrap();
+///
+/// let mut writer = PgnWriter::new(Vec::new()).comments(false).variations(false);
+/// writer.write_game(&game).unwrap();
+/// let written = String::from_utf8(writer.into_inner()).unwrap();
+/// assert!(written.starts_with("[Event \"?\"]\n"));
+/// assert!(written.contains("[White \"Morphy\"]\n"));
+/// assert!(written.ends_with("\n\n1. e4 e5 2. Nf3 *\n\n"));
+/// ```
+pub struct PgnWriter<W> {
+    writer: W,
+    comments: bool,
+    variations: bool,
+    nags: bool,
+    clocks: bool,
+}
+
+impl<W: Write> PgnWriter<W> {
+    /// A writer of games to `writer`, with every annotation included.
+    pub fn new(writer: W) -> PgnWriter<W> {
+        PgnWriter {
+            writer,
+            comments: true,
+            variations: true,
+            nags: true,
+            clocks: true,
+        }
+    }
+
+    /// Sets whether the text of comments is written.
+    ///
+    /// Clock annotations are controlled separately by [`PgnWriter::clocks`].
+    pub fn comments(mut self, include: bool) -> PgnWriter<W> {
+        self.comments = include;
+        self
+    }
+
+    /// Sets whether variations are written.
+    pub fn variations(mut self, include: bool) -> PgnWriter<W> {
+        self.variations = include;
+        self
+    }
+
+    /// Sets whether numeric annotation glyphs are written.
+    pub fn nags(mut self, include: bool) -> PgnWriter<W> {
+        self.nags = include;
+        self
+    }
+
+    /// Sets whether `[%clk]` clock annotations in comments are written.
+    pub fn clocks(mut self, include: bool) -> PgnWriter<W> {
+        self.clocks = include;
+        self
+    }
+
+    /// The underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes `game`, followed by a blank line.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the underlying writer does, or with [`io::ErrorKind::InvalidData`] if the
+    /// `FEN` tag of the game does not hold a valid position.
+    pub fn write_game(&mut self, game: &Game) -> io::Result<()> {
+        let position = game
+            .starting_position()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        let result = game.result.to_string();
+        for (name, unknown) in SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Result" => &result,
+                _ => game.tag(name).unwrap_or(unknown),
+            };
+            write_tag(&mut self.writer, name, value)?;
+        }
+        if let Some(fen) = game.tag("FEN") {
+            write_tag(&mut self.writer, "SetUp", game.tag("SetUp").unwrap_or("1"))?;
+            write_tag(&mut self.writer, "FEN", fen)?;
+        }
+        for (name, value) in &game.tags {
+            let written = SEVEN_TAG_ROSTER.iter().any(|&(roster, _)| roster == name)
+                || (game.tag("FEN").is_some() && (name == "SetUp" || name == "FEN"));
+            if !written {
+                write_tag(&mut self.writer, name, value)?;
+            }
+        }
+        writeln!(self.writer)?;
+
+        let mut movetext = Movetext::default();
+        self.variation(&mut movetext, position, &game.mainline);
+        movetext.push(result);
+        writeln!(self.writer, "{}", movetext.text)?;
+        writeln!(self.writer)
+    }
+
+    /// Appends `variation`, played from `position`, to `movetext`.
+    fn variation(&self, movetext: &mut Movetext, mut position: Position, variation: &Variation) {
+        for comment in &variation.comments {
+            self.comment(movetext, comment);
+        }
+        // the move number is repeated for Black after anything that interrupts the moves
+        let mut interrupted = true;
+        for node in &variation.moves {
+            let number = position.fullmove_number();
+            match position.side_to_move() {
+                Color::White => movetext.push(format!("{number}.")),
+                Color::Black if interrupted => movetext.push(format!("{number}...")),
+                Color::Black => {}
+            }
+            movetext.push(position.san(node.mv));
+            interrupted = self.annotations(movetext, position, node);
+            position.make_move(node.mv);
+        }
+    }
+
+    /// Appends the NAGs, comments and variations of `node`, played from `position`, and
+    /// returns whether any comment or variation was written.
+    fn annotations(&self, movetext: &mut Movetext, position: Position, node: &MoveNode) -> bool {
+        if self.nags {
+            for nag in &node.nags {
+                movetext.push(format!("${nag}"));
+            }
+        }
+        let mut interrupted = false;
+        for comment in &node.comments {
+            interrupted |= self.comment(movetext, comment);
+        }
+        if self.variations {
+            for variation in &node.variations {
+                movetext.open_variation();
+                self.variation(movetext, position, variation);
+                movetext.close_variation();
+                interrupted = true;
+            }
+        }
+        interrupted
+    }
+
+    /// Appends what the options keep of `comment`, returning whether anything was written.
+    fn comment(&self, movetext: &mut Movetext, comment: &str) -> bool {
+        let text = match (self.comments, self.clocks) {
+            (true, true) => comment.to_owned(),
+            (comments, clocks) => {
+                let (clock, rest) = split_clocks(comment);
+                let mut kept = Vec::new();
+                if clocks {
+                    kept.extend(clock);
+                }
+                if comments && !rest.trim().is_empty() {
+                    kept.push(rest.trim().to_owned());
+                }
+                kept.join(" ")
+            }
+        };
+        if text.is_empty() && !(self.comments && self.clocks) {
+            return false;
+        }
+        if !text.contains('}') {
+            movetext.push(format!("{{{text}}}"));
+            return true;
+        }
+        // a brace comment cannot hold `}`, but a rest-of-line comment can: lines holding `}`
+        // are written as those, and the lines between them in braces
+        let mut braced: Vec<&str> = Vec::new();
+        for line in text.split('\n') {
+            if line.contains('}') {
+                if !braced.is_empty() {
+                    movetext.push(format!("{{{}}}", braced.join("\n")));
+                    braced.clear();
+                }
+                movetext.push_line_comment(line);
+            } else {
+                braced.push(line);
+            }
+        }
+        if !braced.is_empty() {
+            movetext.push(format!("{{{}}}", braced.join("\n")));
+        }
+        true
+    }
+}
+
+fn write_tag(writer: &mut impl Write, name: &str, value: &str) -> io::Result<()> {
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+    writeln!(writer, "[{name} \"{escaped}\"]")
+}
+
+/// The `[%clk ...]` commands of `comment`, and the comment with them removed.
+fn split_clocks(comment: &str) -> (Vec<String>, String) {
+    let mut clocks = Vec::new();
+    let mut rest = String::new();
+    let mut remaining = comment;
+    while let Some(start) = remaining.find("[%clk") {
+        let Some(end) = remaining[start..].find(']') else {
+            break;
+        };
+        rest.push_str(&remaining[..start]);
+        clocks.push(remaining[start..=start + end].to_owned());
+        remaining = &remaining[start + end + 1..];
+    }
+    rest.push_str(remaining);
+    (clocks, rest)
+}
+
+/// Movetext being laid out into lines of at most [LINE_WIDTH] columns.
+///
+/// Tokens are never split, so a comment longer than a line gets a line of its own.
+#[derive(Default)]
+struct Movetext {
+    text: String,
+    column: usize,
+    /// Whether the next token follows an opening parenthesis directly.
+    after_open: bool,
+}
+
+impl Movetext {
+    fn push(&mut self, token: impl AsRef<str>) {
+        let token = token.as_ref();
+        let width = token.lines().next().unwrap_or("").chars().count();
+        if self.column > 0 && !self.after_open {
+            if self.column + 1 + width > LINE_WIDTH {
+                self.text.push('\n');
+                self.column = 0;
+            } else {
+                self.text.push(' ');
+                self.column += 1;
+            }
+        }
+        self.text.push_str(token);
+        self.after_open = false;
+        self.column = match token.rfind('\n') {
+            // a multi-line comment continues on its last line
+            Some(newline) => token[newline + 1..].chars().count(),
+            None => self.column + width,
+        };
+    }
+
+    /// Appends `;text`, ending the line after it.
+    fn push_line_comment(&mut self, text: &str) {
+        self.push(format!(";{text}"));
+        self.text.push('\n');
+        self.column = 0;
+    }
+
+    fn open_variation(&mut self) {
+        self.push("(");
+        self.after_open = true;
+    }
+
+    fn close_variation(&mut self) {
+        if self.column + 1 > LINE_WIDTH {
+            self.text.push('\n');
+            self.column = 0;
+        }
+        self.text.push(')');
+        self.column += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgn::PgnReader;
+
+    fn write(
+        game: &Game,
+        configure: impl FnOnce(PgnWriter<Vec<u8>>) -> PgnWriter<Vec<u8>>,
+    ) -> String {
+        let mut writer = configure(PgnWriter::new(Vec::new()));
+        writer.write_game(game).unwrap();
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    fn read(pgn: &str) -> Game {
+        PgnReader::new(pgn.as_bytes()).next().unwrap().unwrap()
+    }
+
+    const ANNOTATED: &str = r#"[Event "Annotated"]
+[Annotator "Someone"]
+[White "A \"quoted\" \\ name"]
+
+{Start} 1. e4 {[%clk 0:03:00] Best by test} e5 $1 (1... c5 {Sicilian} 2. Nf3 (2. c3)
+d6) 2. Nf3! {[%clk 0:02:58]} Nc6 3. Bb5 {A
+long comment} -- 1-0
+"#;
+
+    #[test]
+    fn writes_the_seven_tag_roster_first() {
+        let pgn = write(&read(ANNOTATED), |w| w);
+        let tags: Vec<&str> = pgn.lines().take_while(|line| !line.is_empty()).collect();
+        assert_eq!(
+            tags,
+            [
+                "[Event \"Annotated\"]",
+                "[Site \"?\"]",
+                "[Date \"????.??.??\"]",
+                "[Round \"?\"]",
+                "[White \"A \\\"quoted\\\" \\\\ name\"]",
+                "[Black \"?\"]",
+                "[Result \"1-0\"]",
+                "[Annotator \"Someone\"]",
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trips_everything_the_reader_accepts() {
+        let game = read(ANNOTATED);
+        let pgn = write(&game, |w| w);
+        let reread = read(&pgn);
+        assert_eq!(reread.mainline, game.mainline);
+        assert_eq!(reread.result, game.result);
+        for (name, value) in &game.tags {
+            assert_eq!(reread.tag(name), Some(value.as_str()), "{name}");
+        }
+        assert_eq!(write(&reread, |w| w), pgn);
+    }
+
+    #[test]
+    fn comments_holding_braces_round_trip() {
+        let game = read("1. e4 ; a } b\ne5 *");
+        assert_eq!(game.mainline.moves[0].comments, [" a } b"]);
+        let pgn = write(&game, |w| w);
+        assert!(pgn.contains("1. e4 ; a } b\n1... e5 *"), "{pgn}");
+        assert_eq!(read(&pgn).mainline, game.mainline);
+
+        // a comment spanning lines is split into a rest-of-line comment per line holding `}`
+        let mut game = game;
+        let comment = "a }\nb\nc\n}d";
+        game.mainline.moves[0].comments = vec![comment.to_owned()];
+        let pgn = write(&game, |w| w);
+        assert!(pgn.contains("1. e4 ;a }\n{b\nc} ;}d\n1... e5 *"), "{pgn}");
+        let reread = read(&pgn);
+        assert_eq!(reread.mainline.moves[0].comments, ["a }", "b\nc", "}d"]);
+        assert_eq!(reread.mainline.moves[0].comments.join("\n"), comment);
+    }
+
+    #[test]
+    fn options_strip_annotations() {
+        let game = read(ANNOTATED);
+        let movetext = |pgn: String| pgn.split("\n\n").nth(1).unwrap().to_owned();
+        assert_eq!(
+            movetext(write(&game, |w| w
+                .comments(false)
+                .variations(false)
+                .nags(false)
+                .clocks(false))),
+            "1. e4 e5 2. Nf3 Nc6 3. Bb5 -- 1-0"
+        );
+        assert_eq!(
+            movetext(write(&game, |w| w.comments(false).variations(false))),
+            "1. e4 {[%clk 0:03:00]} 1... e5 $1 2. Nf3 $1 {[%clk 0:02:58]} 2... Nc6 3. Bb5 --\n1-0"
+        );
+        assert_eq!(
+            movetext(write(&game, |w| w
+                .clocks(false)
+                .variations(false)
+                .nags(false))),
+            "{Start} 1. e4 {Best by test} 1... e5 2. Nf3 Nc6 3. Bb5 {A\nlong comment} 3... -- \
+             1-0"
+        );
+    }
+
+    #[test]
+    fn wraps_movetext_at_80_columns() {
+        let mut game = Game::default();
+        let mut position = Position::startpos();
+        for _ in 0..60 {
+            let Some(&mv) = position.legal_moves().first() else {
+                break;
+            };
+            game.mainline.moves.push(MoveNode::new(mv));
+            position.make_move(mv);
+        }
+        let pgn = write(&game, |w| w);
+        assert!(pgn.lines().all(|line| line.chars().count() <= LINE_WIDTH));
+        assert!(pgn.lines().filter(|line| !line.starts_with('[')).count() > 3);
+        assert_eq!(read(&pgn).mainline, game.mainline);
+    }
+
+    #[test]
+    fn custom_positions_get_setup_and_fen() {
+        let fen = "4k3/8/8/8/8/8/8/4K2R b K - 3 40";
+        let position: Position = fen.parse().unwrap();
+        let mut game = Game::from_position(&position);
+        game.mainline
+            .moves
+            .push(MoveNode::new(position.parse_san("Kd7").unwrap()));
+        let pgn = write(&game, |w| w);
+        assert!(pgn.contains(&format!(
+            "[Result \"*\"]\n[SetUp \"1\"]\n[FEN \"{fen}\"]\n\n"
+        )));
+        assert!(pgn.ends_with("\n\n40... Kd7 *\n\n"));
+        assert_eq!(read(&pgn).mainline, game.mainline);
+
+        assert!(Game::from_position(&Position::startpos()).tags.is_empty());
+    }
+}
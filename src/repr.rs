@@ -1,2 +1,7 @@
 //! Representation for engine internals.
-pub mod board;
\ No newline at end of file
+pub mod board;
+pub mod castling;
+pub mod moves;
+pub mod piece;
+pub mod position;
+pub mod zobrist;
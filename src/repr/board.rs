@@ -1,7 +1,200 @@
 //! The chess board.
 //!
 //! The board contains the physical location of the pieces on the board.
+pub mod masks;
 pub mod square;
 mod bitboard;
 
-pub use bitboard::BitBoard;
+use std::fmt;
+use std::fmt::Formatter;
+
+pub use bitboard::{BitBoard, Direction, Squares};
+
+use crate::repr::piece::{Color, Piece, PieceKind};
+use square::Square;
+
+/// A `Board` is a piece-centric board representation built from [`BitBoard`]s.
+///
+/// It keeps one `BitBoard` per [`PieceKind`] and one per [`Color`]; the squares holding a
+/// given piece are the intersection of the two. The union of all pieces is cached so
+/// occupancy queries do not need to combine the other boards.
+///
+/// # Examples
+///
+/// ```
+/// use chess::repr::board::Board;
+/// use chess::repr::board::square::Square;
+/// use chess::repr::piece::Piece;
+/// let mut board = Board::default();
+/// board.put("e2".parse::<Square>().unwrap(), Piece::WhitePawn);
+/// board.move_piece("e2".parse().unwrap(), "e4".parse().unwrap());
+/// assert_eq!(board.piece_at("e4".parse().unwrap()), Some(Piece::WhitePawn));
+/// assert_eq!(board.piece_at("e2".parse().unwrap()), None);
+/// println!("{board}");
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Hash)]
+pub struct Board {
+    pieces: [BitBoard; 6],
+    colors: [BitBoard; 2],
+    occupied: BitBoard,
+}
+
+impl Board {
+    /// The squares occupied by pieces of the given kind, of either color.
+    pub fn pieces(&self, kind: PieceKind) -> BitBoard {
+        self.pieces[kind.index()]
+    }
+
+    /// The squares occupied by pieces of the given color.
+    pub fn color(&self, color: Color) -> BitBoard {
+        self.colors[color.index()]
+    }
+
+    /// The squares occupied by pieces of the given color and kind.
+    pub fn colored_pieces(&self, color: Color, kind: PieceKind) -> BitBoard {
+        self.pieces(kind) & self.color(color)
+    }
+
+    /// The square of the king of the given color, if there is one.
+    pub fn king(&self, color: Color) -> Option<Square> {
+        self.colored_pieces(color, PieceKind::King).lsb()
+    }
+
+    /// The squares occupied by any piece.
+    pub fn occupied(&self) -> BitBoard {
+        self.occupied
+    }
+
+    /// The piece standing on `square`, if any.
+    pub fn piece_at(&self, square: Square) -> Option<Piece> {
+        self.piece_at_index(square.into())
+    }
+
+    /// Places a piece on `square`, returning the piece it replaced, if any.
+    pub fn put(&mut self, square: Square, piece: Piece) -> Option<Piece> {
+        let idx = square.into();
+        let replaced = self.remove_index(idx);
+        self.put_index(idx, piece);
+        replaced
+    }
+
+    /// Removes the piece standing on `square`, returning it, if any.
+    pub fn remove(&mut self, square: Square) -> Option<Piece> {
+        self.remove_index(square.into())
+    }
+
+    /// Moves the piece on `from` to `to`, returning the piece captured on `to`, if any.
+    ///
+    /// Nothing happens if `from` is empty.
+    pub fn move_piece(&mut self, from: Square, to: Square) -> Option<Piece> {
+        let (from, to) = (from.into(), to.into());
+        let piece = self.piece_at_index(from)?;
+        self.remove_index(from);
+        let captured = self.remove_index(to);
+        self.put_index(to, piece);
+        captured
+    }
+
+    fn piece_at_index(&self, idx: u8) -> Option<Piece> {
+        if !self.occupied.is_set(idx) {
+            return None;
+        }
+        let color = if self.color(Color::White).is_set(idx) {
+            Color::White
+        } else {
+            Color::Black
+        };
+        PieceKind::ALL
+            .into_iter()
+            .find(|&kind| self.pieces(kind).is_set(idx))
+            .map(|kind| Piece::new(color, kind))
+    }
+
+    fn put_index(&mut self, idx: u8, piece: Piece) {
+        self.pieces[piece.kind().index()].set(idx);
+        self.colors[piece.color().index()].set(idx);
+        self.occupied.set(idx);
+    }
+
+    fn remove_index(&mut self, idx: u8) -> Option<Piece> {
+        let piece = self.piece_at_index(idx)?;
+        self.pieces[piece.kind().index()].unset(idx);
+        self.colors[piece.color().index()].unset(idx);
+        self.occupied.unset(idx);
+        Some(piece)
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut s: String = "".to_owned();
+        for rank in (0u8..8).rev() {
+            s.push_str(&format!("{} ", rank + 1));
+            for file in 0u8..8 {
+                match self.piece_at_index(rank << 3 | file) {
+                    Some(piece) => s.push_str(&format!("{piece} ")),
+                    None => s.push_str(". "),
+                }
+            }
+            s.push('\n');
+        }
+        let files: String = ('a'..='h').fold(" ".to_owned(), |acc, e| format!("{acc} {e}"));
+        s.push_str(&files);
+        write!(f, "{s}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sq(s: &str) -> Square {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn put_and_remove_keep_occupancy_in_sync() {
+        let mut board = Board::default();
+        assert_eq!(board.put(sq("d1"), Piece::WhiteQueen), None);
+        assert_eq!(board.put(sq("d8"), Piece::BlackQueen), None);
+        assert_eq!(board.occupied().population_count(), 2);
+        assert_eq!(board.pieces(PieceKind::Queen).population_count(), 2);
+        assert_eq!(
+            board
+                .colored_pieces(Color::Black, PieceKind::Queen)
+                .population_count(),
+            1
+        );
+
+        assert_eq!(board.remove(sq("d1")), Some(Piece::WhiteQueen));
+        assert_eq!(board.remove(sq("d1")), None);
+        assert_eq!(board.color(Color::White), BitBoard::default());
+        assert_eq!(board.occupied().population_count(), 1);
+    }
+
+    #[test]
+    fn move_piece_captures() {
+        let mut board = Board::default();
+        board.put(sq("e4"), Piece::WhitePawn);
+        board.put(sq("d5"), Piece::BlackKnight);
+        assert_eq!(
+            board.move_piece(sq("e4"), sq("d5")),
+            Some(Piece::BlackKnight)
+        );
+        assert_eq!(board.piece_at(sq("d5")), Some(Piece::WhitePawn));
+        assert_eq!(board.pieces(PieceKind::Knight), BitBoard::default());
+        assert_eq!(board.occupied().population_count(), 1);
+    }
+
+    #[test]
+    fn display_renders_piece_letters() {
+        let mut board = Board::default();
+        board.put(sq("a1"), Piece::WhiteRook);
+        board.put(sq("h8"), Piece::BlackKing);
+        let rendered = board.to_string();
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!(lines[0], "8 . . . . . . . k ");
+        assert_eq!(lines[7], "1 R . . . . . . . ");
+        assert_eq!(lines[8], "  a b c d e f g h");
+    }
+}
@@ -20,7 +20,14 @@
 //! [piece]: https://en.wikipedia.org/wiki/Glossary_of_chess#piece
 use std::fmt;
 use std::fmt::Formatter;
-use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Mul, Not, Shl};
+use std::iter::FusedIterator;
+use std::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Mul, Not, Shl, ShlAssign, Shr,
+    ShrAssign,
+};
+
+use super::masks;
+use super::square::Square;
 
 /// A `BitBoard` represents occupied and vacant positions on an 8x8 grid.
 ///
@@ -58,6 +65,27 @@ use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, M
 pub struct BitBoard(u64);
 
 impl BitBoard {
+    /// The `BitBoard` with no square occupied.
+    pub const EMPTY: BitBoard = BitBoard(0);
+
+    /// The `BitBoard` with every square occupied.
+    pub const FULL: BitBoard = BitBoard(!0);
+
+    /// Creates a `BitBoard` from its raw bits, using the mapping described on [`BitBoard`].
+    pub const fn new(bits: u64) -> BitBoard {
+        BitBoard(bits)
+    }
+
+    /// Creates a `BitBoard` with only `square` occupied.
+    pub const fn from_square(square: Square) -> BitBoard {
+        BitBoard(1 << square as u8)
+    }
+
+    /// The raw bits of the `BitBoard`.
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
     /// The number of occupied squares on the `BitBoard`.
     ///
     /// # Examples
@@ -65,7 +93,7 @@ impl BitBoard {
     /// ```
     /// use chess::repr::board::BitBoard;
     /// use chess::repr::board::square::{File, Rank, Square};
-    /// let e4 = Square{ file: File::E, rank: Rank::Four};
+    /// let e4 = Square::new(File::E, Rank::Four);
     /// let board = BitBoard::default().with(e4.into());
     /// assert_eq!(board.population_count(), 1);
     /// println!("{board}");
@@ -84,12 +112,259 @@ impl BitBoard {
         self.0 &= !(1 << idx)
     }
 
+    /// Whether a bit in the BitBoard is set.
+    pub fn is_set(&self, idx: u8) -> bool {
+        self.0 & (1 << idx) != 0
+    }
+
     /// Returns a copied BitBoard with the given bit set.
     pub fn with(&self, idx: u8) -> BitBoard {
         let mut board = *self;
         board.set(idx);
         board
     }
+
+    /// Whether no square is occupied.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether `square` is occupied.
+    pub fn contains(&self, square: Square) -> bool {
+        self.is_set(square.into())
+    }
+
+    /// The occupied square with the lowest index, if any.
+    pub fn lsb(&self) -> Option<Square> {
+        if self.is_empty() {
+            return None;
+        }
+        Square::try_from(self.0.trailing_zeros() as u8).ok()
+    }
+
+    /// The occupied square with the highest index, if any.
+    pub fn msb(&self) -> Option<Square> {
+        if self.is_empty() {
+            return None;
+        }
+        Square::try_from(63 - self.0.leading_zeros() as u8).ok()
+    }
+
+    /// Unsets the occupied square with the lowest index and returns it, if any.
+    pub fn pop_lsb(&mut self) -> Option<Square> {
+        let square = self.lsb()?;
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+}
+
+/// Every square except those on the a-file.
+const NOT_FILE_A: u64 = !masks::FILE_A.bits();
+/// Every square except those on the h-file.
+const NOT_FILE_H: u64 = !masks::FILE_H.bits();
+
+/// One of the eight compass directions a [`BitBoard`] can be shifted in.
+///
+/// North points towards the eighth rank and east towards the h-file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    #[allow(missing_docs)]
+    North,
+    #[allow(missing_docs)]
+    NorthEast,
+    #[allow(missing_docs)]
+    East,
+    #[allow(missing_docs)]
+    SouthEast,
+    #[allow(missing_docs)]
+    South,
+    #[allow(missing_docs)]
+    SouthWest,
+    #[allow(missing_docs)]
+    West,
+    #[allow(missing_docs)]
+    NorthWest,
+}
+
+impl Direction {
+    /// Every direction, clockwise from north.
+    pub const ALL: [Direction; 8] = {
+        use self::Direction::*;
+        [
+            North, NorthEast, East, SouthEast, South, SouthWest, West, NorthWest,
+        ]
+    };
+
+    /// The four rook directions.
+    pub const ORTHOGONAL: [Direction; 4] = {
+        use self::Direction::*;
+        [North, East, South, West]
+    };
+
+    /// The four bishop directions.
+    pub const DIAGONAL: [Direction; 4] = {
+        use self::Direction::*;
+        [NorthEast, SouthEast, SouthWest, NorthWest]
+    };
+
+    /// The direction pointing the other way.
+    pub const fn opposite(self) -> Direction {
+        Direction::ALL[(self as usize + 4) % 8]
+    }
+
+    /// The file and rank step of this direction, for use with [`Square::offset`].
+    pub const fn delta(self) -> (i8, i8) {
+        use self::Direction::*;
+        match self {
+            North => (0, 1),
+            NorthEast => (1, 1),
+            East => (1, 0),
+            SouthEast => (1, -1),
+            South => (0, -1),
+            SouthWest => (-1, -1),
+            West => (-1, 0),
+            NorthWest => (-1, 1),
+        }
+    }
+
+    /// The change in square index of a single step in this direction.
+    pub const fn index_delta(self) -> i8 {
+        let (df, dr) = self.delta();
+        dr * 8 + df
+    }
+}
+
+impl BitBoard {
+    /// Moves every square one rank up; squares on the eighth rank fall off the board.
+    #[inline]
+    pub const fn north(self) -> BitBoard {
+        BitBoard(self.0 << 8)
+    }
+
+    /// Moves every square one rank down; squares on the first rank fall off the board.
+    #[inline]
+    pub const fn south(self) -> BitBoard {
+        BitBoard(self.0 >> 8)
+    }
+
+    /// Moves every square one file right; squares on the h-file fall off the board.
+    #[inline]
+    pub const fn east(self) -> BitBoard {
+        BitBoard((self.0 & NOT_FILE_H) << 1)
+    }
+
+    /// Moves every square one file left; squares on the a-file fall off the board.
+    #[inline]
+    pub const fn west(self) -> BitBoard {
+        BitBoard((self.0 & NOT_FILE_A) >> 1)
+    }
+
+    /// Moves every square one step up and right.
+    #[inline]
+    pub const fn north_east(self) -> BitBoard {
+        BitBoard((self.0 & NOT_FILE_H) << 9)
+    }
+
+    /// Moves every square one step up and left.
+    #[inline]
+    pub const fn north_west(self) -> BitBoard {
+        BitBoard((self.0 & NOT_FILE_A) << 7)
+    }
+
+    /// Moves every square one step down and right.
+    #[inline]
+    pub const fn south_east(self) -> BitBoard {
+        BitBoard((self.0 & NOT_FILE_H) >> 7)
+    }
+
+    /// Moves every square one step down and left.
+    #[inline]
+    pub const fn south_west(self) -> BitBoard {
+        BitBoard((self.0 & NOT_FILE_A) >> 9)
+    }
+
+    /// Moves every square one step in `direction`, dropping squares that would leave the
+    /// board rather than wrapping them onto the opposite edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chess::repr::board::{BitBoard, Direction};
+    /// use chess::repr::board::square::Square;
+    /// let board: BitBoard = [Square::H4, Square::D4].into_iter().collect();
+    /// let east: Vec<Square> = board.shift(Direction::East).into_iter().collect();
+    /// assert_eq!(east, vec![Square::E4]);
+    /// ```
+    #[inline]
+    pub const fn shift(self, direction: Direction) -> BitBoard {
+        use self::Direction::*;
+        match direction {
+            North => self.north(),
+            NorthEast => self.north_east(),
+            East => self.east(),
+            SouthEast => self.south_east(),
+            South => self.south(),
+            SouthWest => self.south_west(),
+            West => self.west(),
+            NorthWest => self.north_west(),
+        }
+    }
+}
+
+/// An iterator over the occupied [`Square`]s of a [`BitBoard`], from the lowest index to the
+/// highest.
+///
+/// # Examples
+///
+/// ```
+/// use chess::repr::board::BitBoard;
+/// use chess::repr::board::square::Square;
+/// let board: BitBoard = [Square::E4, Square::A1, Square::H8].into_iter().collect();
+/// let squares: Vec<Square> = board.into_iter().collect();
+/// assert_eq!(squares, vec![Square::A1, Square::E4, Square::H8]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Squares(BitBoard);
+
+impl Iterator for Squares {
+    type Item = Square;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_lsb()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.population_count() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Squares {}
+
+impl FusedIterator for Squares {}
+
+impl IntoIterator for BitBoard {
+    type Item = Square;
+    type IntoIter = Squares;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Squares(self)
+    }
+}
+
+impl FromIterator<Square> for BitBoard {
+    fn from_iter<T: IntoIterator<Item = Square>>(iter: T) -> Self {
+        let mut board = BitBoard::default();
+        board.extend(iter);
+        board
+    }
+}
+
+impl Extend<Square> for BitBoard {
+    fn extend<T: IntoIterator<Item = Square>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|square| self.set(square.into()));
+    }
 }
 
 impl BitAnd for BitBoard {
@@ -158,6 +433,52 @@ impl Shl<i64> for BitBoard {
     }
 }
 
+impl Shr for BitBoard {
+    type Output = BitBoard;
+
+    #[inline(always)]
+    fn shr(self, rhs: Self) -> Self::Output {
+        BitBoard((self.0).wrapping_shr(rhs.0 as u32))
+    }
+}
+
+impl Shr<i64> for BitBoard {
+    type Output = BitBoard;
+
+    #[inline(always)]
+    fn shr(self, rhs: i64) -> Self::Output {
+        BitBoard((self.0).wrapping_shr(rhs as u32))
+    }
+}
+
+impl ShlAssign for BitBoard {
+    #[inline(always)]
+    fn shl_assign(&mut self, rhs: Self) {
+        *self = *self << rhs;
+    }
+}
+
+impl ShlAssign<i64> for BitBoard {
+    #[inline(always)]
+    fn shl_assign(&mut self, rhs: i64) {
+        *self = *self << rhs;
+    }
+}
+
+impl ShrAssign for BitBoard {
+    #[inline(always)]
+    fn shr_assign(&mut self, rhs: Self) {
+        *self = *self >> rhs;
+    }
+}
+
+impl ShrAssign<i64> for BitBoard {
+    #[inline(always)]
+    fn shr_assign(&mut self, rhs: i64) {
+        *self = *self >> rhs;
+    }
+}
+
 impl Mul for BitBoard {
     type Output = Self;
 
@@ -209,6 +530,7 @@ impl fmt::Display for BitBoard {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::repr::board::square::File;
 
     #[test]
     fn popcnt_empty_bitboard() {
@@ -221,4 +543,82 @@ mod tests {
         let full_bitboard = !BitBoard::default();
         assert_eq!(full_bitboard.population_count(), 64)
     }
+
+    #[test]
+    fn iterate_full_bitboard_in_index_order() {
+        let squares: Vec<Square> = (!BitBoard::default()).into_iter().collect();
+        assert_eq!(squares, Square::ALL.to_vec());
+    }
+
+    #[test]
+    fn lsb_msb_of_empty_bitboard() {
+        let mut empty_board = BitBoard::default();
+        assert!(empty_board.is_empty());
+        assert_eq!(empty_board.lsb(), None);
+        assert_eq!(empty_board.msb(), None);
+        assert_eq!(empty_board.pop_lsb(), None);
+    }
+
+    #[test]
+    fn pop_lsb_drains_bitboard() {
+        let mut board: BitBoard = [Square::H8, Square::C3, Square::B7].into_iter().collect();
+        assert_eq!(board.msb(), Some(Square::H8));
+        assert_eq!(board.pop_lsb(), Some(Square::C3));
+        assert!(!board.contains(Square::C3));
+        assert!(board.contains(Square::B7));
+        assert_eq!(board.pop_lsb(), Some(Square::B7));
+        assert_eq!(board.pop_lsb(), Some(Square::H8));
+        assert!(board.is_empty());
+    }
+
+    #[test]
+    fn shifts_do_not_wrap_files() {
+        let h_file: BitBoard = Square::ALL
+            .into_iter()
+            .filter(|sq| sq.file() == File::H)
+            .collect();
+        assert!(h_file.east().is_empty());
+        assert!(h_file.north_east().is_empty());
+        assert!(h_file.south_east().is_empty());
+        assert_eq!(h_file.west().population_count(), 8);
+        let a_file = h_file >> 7;
+        assert!(a_file.west().is_empty());
+        assert!(a_file.north_west().is_empty());
+        assert!(a_file.south_west().is_empty());
+    }
+
+    #[test]
+    fn shift_matches_square_offset() {
+        for square in Square::ALL {
+            let board = BitBoard::default().with(square.into());
+            for direction in Direction::ALL {
+                let (df, dr) = direction.delta();
+                assert_eq!(board.shift(direction).lsb(), square.offset(df, dr));
+                assert_eq!(
+                    board
+                        .shift(direction)
+                        .shift(direction.opposite())
+                        .is_empty(),
+                    square.offset(df, dr).is_none()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn shift_assign_matches_shift() {
+        let mut board = BitBoard::from(0xff);
+        board <<= 8;
+        assert_eq!(board, BitBoard::from(0xff00));
+        board >>= 4;
+        assert_eq!(board, BitBoard::from(0xff0));
+    }
+
+    #[test]
+    fn extend_sets_squares() {
+        let mut board = BitBoard::default().with(Square::A1.into());
+        board.extend([Square::A1, Square::D4]);
+        assert_eq!(board.population_count(), 2);
+        assert_eq!(board.into_iter().len(), 2);
+    }
 }
@@ -0,0 +1,149 @@
+//! Precomputed [BitBoard] masks of commonly used sets of squares.
+//!
+//! Every mask is a `const`, so they can be combined freely at compile time.
+use super::square::Square;
+use super::BitBoard;
+
+/// The a-file.
+pub const FILE_A: BitBoard = BitBoard::new(0x0101_0101_0101_0101);
+/// The b-file.
+pub const FILE_B: BitBoard = BitBoard::new(FILE_A.bits() << 1);
+/// The c-file.
+pub const FILE_C: BitBoard = BitBoard::new(FILE_A.bits() << 2);
+/// The d-file.
+pub const FILE_D: BitBoard = BitBoard::new(FILE_A.bits() << 3);
+/// The e-file.
+pub const FILE_E: BitBoard = BitBoard::new(FILE_A.bits() << 4);
+/// The f-file.
+pub const FILE_F: BitBoard = BitBoard::new(FILE_A.bits() << 5);
+/// The g-file.
+pub const FILE_G: BitBoard = BitBoard::new(FILE_A.bits() << 6);
+/// The h-file.
+pub const FILE_H: BitBoard = BitBoard::new(FILE_A.bits() << 7);
+
+/// The first rank.
+pub const RANK_1: BitBoard = BitBoard::new(0xff);
+/// The second rank.
+pub const RANK_2: BitBoard = BitBoard::new(RANK_1.bits() << 8);
+/// The third rank.
+pub const RANK_3: BitBoard = BitBoard::new(RANK_1.bits() << (8 * 2));
+/// The fourth rank.
+pub const RANK_4: BitBoard = BitBoard::new(RANK_1.bits() << (8 * 3));
+/// The fifth rank.
+pub const RANK_5: BitBoard = BitBoard::new(RANK_1.bits() << (8 * 4));
+/// The sixth rank.
+pub const RANK_6: BitBoard = BitBoard::new(RANK_1.bits() << (8 * 5));
+/// The seventh rank.
+pub const RANK_7: BitBoard = BitBoard::new(RANK_1.bits() << (8 * 6));
+/// The eighth rank.
+pub const RANK_8: BitBoard = BitBoard::new(RANK_1.bits() << (8 * 7));
+
+/// Every file, indexed from the a-file.
+pub const FILES: [BitBoard; 8] = [
+    FILE_A, FILE_B, FILE_C, FILE_D, FILE_E, FILE_F, FILE_G, FILE_H,
+];
+
+/// Every rank, indexed from the first rank.
+pub const RANKS: [BitBoard; 8] = [
+    RANK_1, RANK_2, RANK_3, RANK_4, RANK_5, RANK_6, RANK_7, RANK_8,
+];
+
+/// The light squares, such as h1 and a8.
+pub const LIGHT_SQUARES: BitBoard = BitBoard::new(0x55aa_55aa_55aa_55aa);
+/// The dark squares, such as a1 and h8.
+pub const DARK_SQUARES: BitBoard = BitBoard::new(!LIGHT_SQUARES.bits());
+
+/// The four central squares d4, e4, d5 and e5.
+pub const CENTER: BitBoard =
+    BitBoard::new((FILE_D.bits() | FILE_E.bits()) & (RANK_4.bits() | RANK_5.bits()));
+
+/// The squares on the outer ring of the board.
+pub const EDGES: BitBoard =
+    BitBoard::new(FILE_A.bits() | FILE_H.bits() | RANK_1.bits() | RANK_8.bits());
+
+/// The a1-h8 diagonal.
+pub const MAIN_DIAGONAL: BitBoard = BitBoard::new(0x8040_2010_0804_0201);
+/// The a8-h1 anti-diagonal.
+pub const MAIN_ANTI_DIAGONAL: BitBoard = BitBoard::new(0x0102_0408_1020_4080);
+
+/// For each square, the diagonal running through it from the lower left to the upper right.
+pub const DIAGONALS: [BitBoard; 64] = {
+    let mut masks = [BitBoard::EMPTY; 64];
+    let mut idx = 0;
+    while idx < 64 {
+        let square = Square::ALL[idx];
+        let shift = square.file() as i32 - square.rank() as i32;
+        // shifting east by `shift` files moves the main diagonal down by as many ranks
+        masks[idx] = BitBoard::new(if shift >= 0 {
+            MAIN_DIAGONAL.bits() >> (shift * 8)
+        } else {
+            MAIN_DIAGONAL.bits() << (-shift * 8)
+        });
+        idx += 1;
+    }
+    masks
+};
+
+/// For each square, the anti-diagonal running through it from the upper left to the lower
+/// right.
+pub const ANTI_DIAGONALS: [BitBoard; 64] = {
+    let mut masks = [BitBoard::EMPTY; 64];
+    let mut idx = 0;
+    while idx < 64 {
+        let square = Square::ALL[idx];
+        let shift = square.file() as i32 + square.rank() as i32 - 9;
+        masks[idx] = BitBoard::new(if shift >= 0 {
+            MAIN_ANTI_DIAGONAL.bits() << (shift * 8)
+        } else {
+            MAIN_ANTI_DIAGONAL.bits() >> (-shift * 8)
+        });
+        idx += 1;
+    }
+    masks
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_and_ranks_partition_the_board() {
+        for square in Square::ALL {
+            assert_eq!(FILES.iter().filter(|m| m.contains(square)).count(), 1);
+            assert_eq!(RANKS.iter().filter(|m| m.contains(square)).count(), 1);
+            assert!(square.file_mask().contains(square));
+            assert!(square.rank_mask().contains(square));
+            assert_eq!(
+                LIGHT_SQUARES.contains(square),
+                !DARK_SQUARES.contains(square)
+            );
+        }
+        assert!(DARK_SQUARES.contains(Square::A1));
+        assert!(LIGHT_SQUARES.contains(Square::H1));
+        assert_eq!(EDGES.population_count(), 28);
+        assert_eq!(
+            CENTER.into_iter().collect::<Vec<_>>(),
+            vec![Square::D4, Square::E4, Square::D5, Square::E5]
+        );
+    }
+
+    #[test]
+    fn diagonals_match_square_offsets() {
+        for square in Square::ALL {
+            let mut diagonal = BitBoard::from_square(square);
+            let mut anti_diagonal = BitBoard::from_square(square);
+            for step in 1..8 {
+                for sign in [-1, 1] {
+                    if let Some(sq) = square.offset(step * sign, step * sign) {
+                        diagonal.set(sq.into());
+                    }
+                    if let Some(sq) = square.offset(-step * sign, step * sign) {
+                        anti_diagonal.set(sq.into());
+                    }
+                }
+            }
+            assert_eq!(DIAGONALS[square.index()], diagonal, "{square}");
+            assert_eq!(ANTI_DIAGONALS[square.index()], anti_diagonal, "{square}");
+        }
+    }
+}
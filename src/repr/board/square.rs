@@ -1,56 +1,169 @@
 //! Representation for board locations.
 
+use std::fmt;
+use std::fmt::Formatter;
 use std::str::FromStr;
 
+use thiserror::Error;
+
+use super::{masks, BitBoard};
+
+/// An error raised when parsing a [`Square`], [`Rank`] or [`File`] from text.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Error)]
+pub enum ParseSquareError {
+    /// The input is not exactly two characters long.
+    #[error("expected 2 characters, found {0}")]
+    BadLength(usize),
+    /// The input contains non-ASCII characters.
+    #[error("non-ASCII input")]
+    NonAscii,
+    /// The character does not name a file.
+    #[error("invalid file {0:?}, expected one of a-h")]
+    InvalidFile(char),
+    /// The character does not name a rank.
+    #[error("invalid rank {0:?}, expected one of 1-8")]
+    InvalidRank(char),
+}
+
 /// A `Square` represents a pair of [`Rank`] and [`File`] that describes a location
 /// on the [`Board`].
 ///
+/// A `Square` is stored as its index into a [`BitBoard`], using the same little-endian
+/// rank-file mapping: `A1` is 0, `H1` is 7 and `H8` is 63.
+///
+/// # Examples
+///
+/// ```
+/// use chess::repr::board::square::{File, Rank, Square};
+/// let e4 = Square::new(File::E, Rank::Four);
+/// assert_eq!(e4, Square::E4);
+/// assert_eq!(e4.offset(1, 1), Some(Square::F5));
+/// assert_eq!(Square::H8.offset(1, 0), None);
+/// assert_eq!(e4.flip_vertical(), Square::E5);
+/// ```
+///
 /// [`Rank`]: Rank
 /// [`File`]: File
 /// [`Board`]: super::Board
-#[derive(Debug)]
-pub struct Square {
+/// [`BitBoard`]: super::BitBoard
+#[rustfmt::skip]
+#[allow(missing_docs)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Square {
+    A1, B1, C1, D1, E1, F1, G1, H1,
+    A2, B2, C2, D2, E2, F2, G2, H2,
+    A3, B3, C3, D3, E3, F3, G3, H3,
+    A4, B4, C4, D4, E4, F4, G4, H4,
+    A5, B5, C5, D5, E5, F5, G5, H5,
+    A6, B6, C6, D6, E6, F6, G6, H6,
+    A7, B7, C7, D7, E7, F7, G7, H7,
+    A8, B8, C8, D8, E8, F8, G8, H8,
+}
+
+impl Square {
+    /// Every square, in index order.
+    #[rustfmt::skip]
+    pub const ALL: [Square; 64] = {
+        use self::Square::*;
+        [
+            A1, B1, C1, D1, E1, F1, G1, H1,
+            A2, B2, C2, D2, E2, F2, G2, H2,
+            A3, B3, C3, D3, E3, F3, G3, H3,
+            A4, B4, C4, D4, E4, F4, G4, H4,
+            A5, B5, C5, D5, E5, F5, G5, H5,
+            A6, B6, C6, D6, E6, F6, G6, H6,
+            A7, B7, C7, D7, E7, F7, G7, H7,
+            A8, B8, C8, D8, E8, F8, G8, H8,
+        ]
+    };
+
+    /// The square on the given file and rank.
+    pub const fn new(file: File, rank: Rank) -> Square {
+        Square::ALL[((rank as usize - 1) << 3) | (file as usize - 1)]
+    }
+
     /// The `File` this `Square` resides on.
-    pub file: File,
+    pub const fn file(self) -> File {
+        File::ALL[self as usize & 0b111]
+    }
+
     /// The `Rank` this `Square` resides on.
-    pub rank: Rank,
+    pub const fn rank(self) -> Rank {
+        Rank::ALL[self as usize >> 3]
+    }
+
+    /// The squares on the same file as this square.
+    pub const fn file_mask(self) -> BitBoard {
+        masks::FILES[self as usize & 0b111]
+    }
+
+    /// The squares on the same rank as this square.
+    pub const fn rank_mask(self) -> BitBoard {
+        masks::RANKS[self as usize >> 3]
+    }
+
+    /// The index of this square, for use as an array key.
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The square `df` files and `dr` ranks away, or `None` if that is off the board.
+    ///
+    /// Positive offsets point towards the h-file and the eighth rank.
+    pub const fn offset(self, df: i8, dr: i8) -> Option<Square> {
+        // widened so that no offset overflows
+        let file = (self as i16 & 0b111) + df as i16;
+        let rank = (self as i16 >> 3) + dr as i16;
+        if file < 0 || file >= 8 || rank < 0 || rank >= 8 {
+            return None;
+        }
+        Some(Square::ALL[(rank * 8 + file) as usize])
+    }
+
+    /// The square mirrored across the horizontal center line, e.g. `E2` becomes `E7`.
+    pub const fn flip_vertical(self) -> Square {
+        Square::ALL[self as usize ^ 0b111000]
+    }
 }
 
 impl From<Square> for u8 {
     /// Map a `Square` to a u8 index for use in `BitBoard`.
     fn from(value: Square) -> Self {
-        let rank = value.rank as u8;
-        let file: u8 = value.file.into();
+        value as u8
+    }
+}
 
-        const WIDTH: u8 = 8;
+impl TryFrom<u8> for Square {
+    type Error = ();
 
-        ((rank - 1) * WIDTH) + file - 1
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Square::ALL.get(value as usize).copied().ok_or(())
     }
 }
 
 impl FromStr for Square {
-    type Err = String;
+    type Err = ParseSquareError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 2 {
-            return Err(format!("Length is not 2; len: {}, &str: {}", s.len(), s));
-        }
         if !s.is_ascii() {
-            return Err("Non-ASCII input".to_owned());
+            return Err(ParseSquareError::NonAscii);
         }
-
-        let mut cs = s.chars();
-        let Some(file) = cs.next() else {
-            return Err("Empty first character.".to_owned())
-        };
-        let Some(rank) = cs.next() else {
-            return Err("Empty second character.".to_owned())
+        let &[file, rank] = s.as_bytes() else {
+            return Err(ParseSquareError::BadLength(s.len()));
         };
 
-        Ok(Square {
-            rank: rank.try_into().expect("unable to parse rank"),
-            file: file.try_into().expect("unable to parse file"),
-        })
+        Ok(Square::new(
+            char::from(file).try_into()?,
+            char::from(rank).try_into()?,
+        ))
+    }
+}
+
+impl fmt::Display for Square {
+    /// Writes the square in algebraic notation, e.g. `e4`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.file().to_char(), self.rank().to_char())
     }
 }
 
@@ -61,7 +174,7 @@ impl FromStr for Square {
 /// whereas Black calls the same rank the "eighth" (or last) rank.
 ///
 /// [Rank]: https://en.wikipedia.org/wiki/Glossary_of_chess#rank
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Rank {
     #[allow(missing_docs)]
     One = 1,
@@ -81,6 +194,19 @@ pub enum Rank {
     Eight,
 }
 
+impl Rank {
+    /// Every rank, from White's side of the board.
+    pub const ALL: [Rank; 8] = {
+        use self::Rank::*;
+        [One, Two, Three, Four, Five, Six, Seven, Eight]
+    };
+
+    /// The digit naming this rank in algebraic notation.
+    pub const fn to_char(self) -> char {
+        (b'0' + self as u8) as char
+    }
+}
+
 impl TryFrom<u8> for Rank {
     type Error = ();
 
@@ -102,13 +228,13 @@ impl TryFrom<u8> for Rank {
 }
 
 impl TryFrom<char> for Rank {
-    type Error = String;
+    type Error = ParseSquareError;
 
     fn try_from(value: char) -> Result<Self, Self::Error> {
-        if ('1'..='8').contains(&value) {
-            return Ok((value.to_digit(10).unwrap() as u8).try_into().unwrap());
-        }
-        Err(String::new())
+        let digit = value
+            .to_digit(10)
+            .ok_or(ParseSquareError::InvalidRank(value))?;
+        Self::try_from(digit as u8).map_err(|_| ParseSquareError::InvalidRank(value))
     }
 }
 
@@ -117,7 +243,7 @@ impl TryFrom<char> for Rank {
 /// Each [file] is named using its position in algebraic notation, a–h.
 ///
 /// [file]: https://en.wikipedia.org/wiki/Glossary_of_chess#file
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum File {
     #[allow(missing_docs)]
     A = 1,
@@ -137,6 +263,19 @@ pub enum File {
     H,
 }
 
+impl File {
+    /// Every file, from the queen side of the board.
+    pub const ALL: [File; 8] = {
+        use self::File::*;
+        [A, B, C, D, E, F, G, H]
+    };
+
+    /// The lowercase letter naming this file in algebraic notation.
+    pub const fn to_char(self) -> char {
+        (b'a' + self as u8 - 1) as char
+    }
+}
+
 impl TryFrom<u8> for File {
     type Error = ();
 
@@ -158,11 +297,14 @@ impl TryFrom<u8> for File {
 }
 
 impl TryFrom<char> for File {
-    type Error = ();
+    type Error = ParseSquareError;
 
     fn try_from(value: char) -> Result<Self, Self::Error> {
-        let lowercase = value.to_lowercase().next().expect("Multi-byte ToLowercase");
-        Self::try_from(lowercase as u8 - b'a' + 1)
+        let lowercase = value.to_ascii_lowercase();
+        if !lowercase.is_ascii_lowercase() {
+            return Err(ParseSquareError::InvalidFile(value));
+        }
+        Self::try_from(lowercase as u8 - b'a' + 1).map_err(|_| ParseSquareError::InvalidFile(value))
     }
 }
 
@@ -182,3 +324,100 @@ impl From<File> for u8 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_valid_squares() {
+        assert_eq!("a1".parse(), Ok(Square::A1));
+        assert_eq!("H8".parse(), Ok(Square::H8));
+        assert_eq!(u8::from(Square::H8), 63);
+    }
+
+    #[test]
+    fn index_matches_file_and_rank() {
+        for (idx, square) in Square::ALL.into_iter().enumerate() {
+            assert_eq!(square.index(), idx);
+            assert_eq!(Square::try_from(idx as u8), Ok(square));
+            assert_eq!(Square::new(square.file(), square.rank()), square);
+            assert_eq!(square.to_string().parse(), Ok(square));
+        }
+        assert_eq!(Square::try_from(64), Err(()));
+    }
+
+    #[test]
+    fn offset_stays_on_board() {
+        assert_eq!(Square::B1.offset(-1, 2), Some(Square::A3));
+        assert_eq!(Square::B1.offset(-2, 1), None);
+        assert_eq!(Square::H4.offset(1, 0), None);
+        assert_eq!(Square::D8.offset(0, 1), None);
+        assert_eq!(Square::D1.offset(0, -1), None);
+        assert_eq!(Square::A1.offset(7, 7), Some(Square::H8));
+        assert_eq!(Square::H8.offset(i8::MAX, 0), None);
+        assert_eq!(Square::H8.offset(0, i8::MAX), None);
+        assert_eq!(Square::A1.offset(i8::MIN, i8::MIN), None);
+        assert_eq!(Square::H8.offset(i8::MIN, 0), None);
+    }
+
+    #[test]
+    fn flip_vertical_mirrors_ranks() {
+        assert_eq!(Square::A1.flip_vertical(), Square::A8);
+        assert_eq!(Square::G7.flip_vertical(), Square::G2);
+        for square in Square::ALL {
+            assert_eq!(square.flip_vertical().flip_vertical(), square);
+            assert_eq!(square.flip_vertical().file(), square.file());
+        }
+    }
+
+    #[test]
+    fn parse_rejects_bad_length() {
+        assert_eq!("".parse::<Square>(), Err(ParseSquareError::BadLength(0)));
+        assert_eq!("e".parse::<Square>(), Err(ParseSquareError::BadLength(1)));
+        assert_eq!("e44".parse::<Square>(), Err(ParseSquareError::BadLength(3)));
+    }
+
+    #[test]
+    fn parse_rejects_non_ascii() {
+        assert_eq!("é4".parse::<Square>(), Err(ParseSquareError::NonAscii));
+        assert_eq!("♔1".parse::<Square>(), Err(ParseSquareError::NonAscii));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_file() {
+        assert_eq!(
+            "z9".parse::<Square>(),
+            Err(ParseSquareError::InvalidFile('z'))
+        );
+        assert_eq!(
+            "`1".parse::<Square>(),
+            Err(ParseSquareError::InvalidFile('`'))
+        );
+        assert_eq!(
+            "11".parse::<Square>(),
+            Err(ParseSquareError::InvalidFile('1'))
+        );
+        assert_eq!(
+            File::try_from('\0'),
+            Err(ParseSquareError::InvalidFile('\0'))
+        );
+    }
+
+    #[test]
+    fn parse_rejects_invalid_rank() {
+        assert_eq!(
+            "a0".parse::<Square>(),
+            Err(ParseSquareError::InvalidRank('0'))
+        );
+        assert_eq!(
+            "a9".parse::<Square>(),
+            Err(ParseSquareError::InvalidRank('9'))
+        );
+        assert_eq!(
+            "ax".parse::<Square>(),
+            Err(ParseSquareError::InvalidRank('x'))
+        );
+        assert_eq!(Rank::try_from('٣'), Err(ParseSquareError::InvalidRank('٣')));
+    }
+}
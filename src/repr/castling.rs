@@ -0,0 +1,380 @@
+//! Representation for castling rights.
+//!
+//! Rights are stored as the files of the rooks that may still castle, so standard chess and
+//! [Chess960] share a single code path: in standard chess the rooks simply start on the a- and
+//! h-files. Wherever the king and rook start, castling ends with them on the same squares as
+//! in standard chess.
+//!
+//! [Chess960]: https://en.wikipedia.org/wiki/Chess960
+
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::attacks::between;
+use crate::repr::board::square::{File, Rank, Square};
+use crate::repr::board::{BitBoard, Board};
+use crate::repr::piece::{Color, Piece, PieceKind};
+
+/// The side of the board a king castles towards.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CastlingSide {
+    /// Castling towards the h-file, `O-O`.
+    KingSide,
+    /// Castling towards the a-file, `O-O-O`.
+    QueenSide,
+}
+
+impl CastlingSide {
+    /// Both sides, king side first.
+    pub const ALL: [CastlingSide; 2] = [CastlingSide::KingSide, CastlingSide::QueenSide];
+
+    /// The side a rook on `rook` stands on, relative to a king on `king`.
+    pub fn of(king: File, rook: File) -> CastlingSide {
+        if rook > king {
+            CastlingSide::KingSide
+        } else {
+            CastlingSide::QueenSide
+        }
+    }
+
+    /// The square the king of `color` ends up on after castling towards this side.
+    pub const fn king_destination(self, color: Color) -> Square {
+        let file = match self {
+            CastlingSide::KingSide => File::G,
+            CastlingSide::QueenSide => File::C,
+        };
+        Square::new(file, back_rank(color))
+    }
+
+    /// The square the rook of `color` ends up on after castling towards this side.
+    pub const fn rook_destination(self, color: Color) -> Square {
+        let file = match self {
+            CastlingSide::KingSide => File::F,
+            CastlingSide::QueenSide => File::D,
+        };
+        Square::new(file, back_rank(color))
+    }
+
+    /// The destination of the move castling the king on `king` with the rook on `rook`
+    /// towards this side.
+    ///
+    /// Castling from the standard starting squares is the king's move, e.g. `e1g1`, as in
+    /// UCI. Any other castling is the king capturing its own rook, e.g. `b1a1`: the king's
+    /// move could coincide with a plain king move, or have the king stay where it stands.
+    pub fn move_destination(self, king: Square, rook: Square) -> Square {
+        let standard_rook = match self {
+            CastlingSide::KingSide => File::H,
+            CastlingSide::QueenSide => File::A,
+        };
+        if king.file() == File::E && rook.file() == standard_rook {
+            self.king_destination(color_of_rank(king.rank()))
+        } else {
+            rook
+        }
+    }
+
+    /// The squares that must be empty for the king on `king` to castle with the rook on
+    /// `rook` towards this side.
+    ///
+    /// These are the squares either piece passes over or lands on, other than the squares
+    /// the two castling pieces stand on.
+    pub fn empty_squares(self, king: Square, rook: Square) -> BitBoard {
+        let color = color_of_rank(king.rank());
+        let (king_to, rook_to) = (self.king_destination(color), self.rook_destination(color));
+        let path = between(king, king_to)
+            | BitBoard::from_square(king_to)
+            | between(rook, rook_to)
+            | BitBoard::from_square(rook_to);
+        path & !(BitBoard::from_square(king) | BitBoard::from_square(rook))
+    }
+
+    /// The squares the king on `king` must not be attacked on to castle towards this side:
+    /// the squares it starts on, passes over and lands on.
+    pub fn unattacked_squares(self, king: Square) -> BitBoard {
+        let king_to = self.king_destination(color_of_rank(king.rank()));
+        between(king, king_to) | BitBoard::from_square(king) | BitBoard::from_square(king_to)
+    }
+}
+
+/// The rank the pieces of `color` start on.
+const fn back_rank(color: Color) -> Rank {
+    match color {
+        Color::White => Rank::One,
+        Color::Black => Rank::Eight,
+    }
+}
+
+/// The color whose back rank is `rank`; only meaningful for the first and eighth ranks.
+fn color_of_rank(rank: Rank) -> Color {
+    if rank == Rank::Eight {
+        Color::Black
+    } else {
+        Color::White
+    }
+}
+
+/// The set of rooks each side may still castle with, by file.
+///
+/// Rights are lost once the king or the corresponding rook has moved; they say nothing about
+/// whether castling is currently possible.
+///
+/// # Examples
+///
+/// ```
+/// use chess::repr::board::square::{File, Square};
+/// use chess::repr::castling::{CastlingRights, CastlingSide};
+/// use chess::repr::piece::Color;
+/// let rights = CastlingRights::rook(Color::White, File::B).union(CastlingRights::BLACK_KINGSIDE);
+/// assert_eq!(rights.to_string(), "Bk");
+/// assert_eq!(
+///     rights.rook_square(Color::White, CastlingSide::QueenSide, Square::D1),
+///     Some(Square::B1)
+/// );
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct CastlingRights(u16);
+
+impl CastlingRights {
+    /// No side may castle.
+    pub const NONE: CastlingRights = CastlingRights(0);
+    /// White may castle with the rook on the h-file.
+    pub const WHITE_KINGSIDE: CastlingRights = CastlingRights::rook(Color::White, File::H);
+    /// White may castle with the rook on the a-file.
+    pub const WHITE_QUEENSIDE: CastlingRights = CastlingRights::rook(Color::White, File::A);
+    /// Black may castle with the rook on the h-file.
+    pub const BLACK_KINGSIDE: CastlingRights = CastlingRights::rook(Color::Black, File::H);
+    /// Black may castle with the rook on the a-file.
+    pub const BLACK_QUEENSIDE: CastlingRights = CastlingRights::rook(Color::Black, File::A);
+    /// Both sides may castle either way, with the rooks of standard chess.
+    pub const ALL: CastlingRights = CastlingRights(
+        Self::WHITE_KINGSIDE.0
+            | Self::WHITE_QUEENSIDE.0
+            | Self::BLACK_KINGSIDE.0
+            | Self::BLACK_QUEENSIDE.0,
+    );
+
+    /// The single right for `color` to castle with the rook on `file`.
+    pub const fn rook(color: Color, file: File) -> CastlingRights {
+        CastlingRights(1 << (color.index() * 8 + file as usize - 1))
+    }
+
+    /// Every right of `color`, whichever file its rooks are on.
+    pub const fn color(color: Color) -> CastlingRights {
+        CastlingRights(0xff << (color.index() * 8))
+    }
+
+    /// Whether `color` may still castle with the rook on `file`.
+    pub const fn has_rook(self, color: Color, file: File) -> bool {
+        self.contains(Self::rook(color, file))
+    }
+
+    /// The files of the rooks `color` may still castle with, from the queen side.
+    pub fn rook_files(self, color: Color) -> impl DoubleEndedIterator<Item = File> {
+        File::ALL
+            .into_iter()
+            .filter(move |&file| self.has_rook(color, file))
+    }
+
+    /// The rook `color` may castle with towards `side` when its king stands on `king`.
+    ///
+    /// If several rooks on that side have the right, the outermost one is chosen.
+    pub fn rook_square(self, color: Color, side: CastlingSide, king: Square) -> Option<Square> {
+        let mut files = self
+            .rook_files(color)
+            .filter(|&file| CastlingSide::of(king.file(), file) == side && file != king.file());
+        let file = match side {
+            CastlingSide::KingSide => files.next_back(),
+            CastlingSide::QueenSide => files.next(),
+        };
+        file.map(|file| Square::new(file, back_rank(color)))
+    }
+
+    /// Whether every right in `other` is also in `self`.
+    pub const fn contains(self, other: CastlingRights) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether no side may castle.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The rights in either `self` or `other`.
+    pub const fn union(self, other: CastlingRights) -> CastlingRights {
+        CastlingRights(self.0 | other.0)
+    }
+
+    /// Adds the rights in `other`.
+    pub fn insert(&mut self, other: CastlingRights) {
+        self.0 |= other.0;
+    }
+
+    /// Removes the rights in `other`.
+    pub fn remove(&mut self, other: CastlingRights) {
+        self.0 &= !other.0;
+    }
+
+    /// The raw bits of the set: one per rook file from the a-file, White's in the low byte.
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    /// The rights in Shredder-FEN notation, naming every rook by its file, e.g. `HAha`.
+    pub fn to_shredder(self) -> String {
+        if self.is_empty() {
+            return "-".to_owned();
+        }
+        let mut out = String::new();
+        for color in Color::ALL {
+            for file in self.rook_files(color).rev() {
+                out.push(letter(color, file.to_char()));
+            }
+        }
+        out
+    }
+
+    /// The rights in X-FEN notation.
+    ///
+    /// A right for the outermost rook on either side of the king is written `KQkq`, as in
+    /// standard FEN; any other rook is named by its file. The rooks are looked up on `board`.
+    pub fn to_xfen(self, board: &Board) -> String {
+        if self.is_empty() {
+            return "-".to_owned();
+        }
+        let mut out = String::new();
+        for color in Color::ALL {
+            let king = board
+                .king(color)
+                .filter(|king| king.rank() == back_rank(color));
+            for file in self.rook_files(color).rev() {
+                let outermost = king.and_then(|king| {
+                    let side = CastlingSide::of(king.file(), file);
+                    (outermost_rook(board, color, side, king) == Some(file)).then_some(side)
+                });
+                let c = match outermost {
+                    Some(CastlingSide::KingSide) => 'k',
+                    Some(CastlingSide::QueenSide) => 'q',
+                    None => file.to_char(),
+                };
+                out.push(letter(color, c));
+            }
+        }
+        out
+    }
+}
+
+/// The file of the outermost rook of `color` towards `side` of the king on `king`, if any.
+pub(crate) fn outermost_rook(
+    board: &Board,
+    color: Color,
+    side: CastlingSide,
+    king: Square,
+) -> Option<File> {
+    let rook = Piece::new(color, PieceKind::Rook);
+    let mut files = File::ALL.into_iter().filter(|&file| {
+        file != king.file()
+            && CastlingSide::of(king.file(), file) == side
+            && board.piece_at(Square::new(file, king.rank())) == Some(rook)
+    });
+    match side {
+        CastlingSide::KingSide => files.next_back(),
+        CastlingSide::QueenSide => files.next(),
+    }
+}
+
+/// `c` in the case FEN uses for `color`.
+fn letter(color: Color, c: char) -> char {
+    match color {
+        Color::White => c.to_ascii_uppercase(),
+        Color::Black => c.to_ascii_lowercase(),
+    }
+}
+
+impl fmt::Display for CastlingRights {
+    /// Writes the rights in FEN notation, e.g. `KQkq` or `-`.
+    ///
+    /// Rooks on the h- and a-files are written `K` and `Q`; without a board to tell which
+    /// rook is outermost, any other rook is named by its file. Use
+    /// [`CastlingRights::to_xfen`] or [`CastlingRights::to_shredder`] for a specific notation.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "-");
+        }
+        for color in Color::ALL {
+            for file in self.rook_files(color).rev() {
+                let c = match file {
+                    File::H => 'k',
+                    File::A => 'q',
+                    _ => file.to_char(),
+                };
+                write!(f, "{}", letter(color, c))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notations() {
+        let board = *"rk2r3/8/8/8/8/8/8/1R2KR1R w - - 0 1"
+            .parse::<crate::repr::position::Position>()
+            .unwrap()
+            .board();
+        let rights = CastlingRights::rook(Color::White, File::B)
+            .union(CastlingRights::rook(Color::White, File::F))
+            .union(CastlingRights::rook(Color::Black, File::A))
+            .union(CastlingRights::rook(Color::Black, File::E));
+        assert_eq!(rights.to_shredder(), "FBea");
+        assert_eq!(rights.to_xfen(&board), "FQkq");
+        assert_eq!(rights.to_string(), "FBeq");
+        let startpos = crate::repr::position::Position::startpos();
+        assert_eq!(CastlingRights::ALL.to_xfen(startpos.board()), "KQkq");
+        assert_eq!(CastlingRights::ALL.to_shredder(), "HAha");
+        assert_eq!(CastlingRights::NONE.to_shredder(), "-");
+    }
+
+    #[test]
+    fn rook_square_picks_the_outermost_rook_on_the_side() {
+        let rights = CastlingRights::rook(Color::Black, File::A)
+            .union(CastlingRights::rook(Color::Black, File::C))
+            .union(CastlingRights::rook(Color::Black, File::G));
+        let rook = |side| rights.rook_square(Color::Black, side, Square::D8);
+        assert_eq!(rook(CastlingSide::QueenSide), Some(Square::A8));
+        assert_eq!(rook(CastlingSide::KingSide), Some(Square::G8));
+        assert_eq!(
+            rights.rook_square(Color::White, CastlingSide::KingSide, Square::E1),
+            None
+        );
+    }
+
+    #[test]
+    fn castling_paths() {
+        let standard = CastlingSide::QueenSide.empty_squares(Square::E1, Square::A1);
+        assert_eq!(
+            standard,
+            [Square::B1, Square::C1, Square::D1].into_iter().collect()
+        );
+        assert_eq!(
+            CastlingSide::QueenSide.unattacked_squares(Square::E1),
+            [Square::C1, Square::D1, Square::E1].into_iter().collect()
+        );
+        // the king on b8 crosses to c8 while the rook on a8 passes over it to d8
+        assert_eq!(
+            CastlingSide::QueenSide.empty_squares(Square::B8, Square::A8),
+            [Square::C8, Square::D8].into_iter().collect()
+        );
+        // the king on g1 stays put while the rook on h1 jumps over it to f1
+        assert_eq!(
+            CastlingSide::KingSide.empty_squares(Square::G1, Square::H1),
+            [Square::F1].into_iter().collect()
+        );
+        assert_eq!(
+            CastlingSide::KingSide.unattacked_squares(Square::G1),
+            BitBoard::from_square(Square::G1)
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Representation for chess moves.
+
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::repr::board::square::{ParseSquareError, Square};
+use crate::repr::piece::PieceKind;
+
+/// An error raised when parsing a [`Move`] from UCI long algebraic notation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Error)]
+pub enum ParseMoveError {
+    /// The input is not 4 or 5 characters long.
+    #[error("expected 4 or 5 characters, found {0}")]
+    BadLength(usize),
+    /// The input contains a character that is not ASCII.
+    #[error("invalid character {0:?}, expected ASCII")]
+    NonAscii(char),
+    /// The origin or destination is not a square.
+    #[error("invalid square: {0}")]
+    InvalidSquare(#[from] ParseSquareError),
+    /// The promotion suffix is not one of `nbrq`.
+    #[error("invalid promotion {0:?}, expected one of `nbrq`")]
+    InvalidPromotion(char),
+}
+
+/// What kind of move a [`Move`] is, beyond its origin and destination.
+///
+/// The values follow the common 4-bit layout where bit 2 marks captures and bit 3 marks
+/// promotions, whose low two bits select the promoted piece.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum MoveFlag {
+    /// A move that captures nothing and is not special.
+    Quiet = 0,
+    /// A pawn advancing two squares from its starting rank.
+    DoublePawnPush = 1,
+    /// Castling towards the h-file.
+    KingCastle = 2,
+    /// Castling towards the a-file.
+    QueenCastle = 3,
+    /// A move capturing the piece on its destination.
+    Capture = 4,
+    /// A pawn capturing a pawn that just passed it with a double push.
+    EnPassant = 5,
+    #[allow(missing_docs)]
+    KnightPromotion = 8,
+    #[allow(missing_docs)]
+    BishopPromotion = 9,
+    #[allow(missing_docs)]
+    RookPromotion = 10,
+    #[allow(missing_docs)]
+    QueenPromotion = 11,
+    #[allow(missing_docs)]
+    KnightPromotionCapture = 12,
+    #[allow(missing_docs)]
+    BishopPromotionCapture = 13,
+    #[allow(missing_docs)]
+    RookPromotionCapture = 14,
+    #[allow(missing_docs)]
+    QueenPromotionCapture = 15,
+}
+
+impl MoveFlag {
+    const CAPTURE_BIT: u8 = 0b0100;
+    const PROMOTION_BIT: u8 = 0b1000;
+
+    /// The flag for promoting to `kind`, capturing or not.
+    ///
+    /// # Panics
+    ///
+    /// If `kind` is a pawn or a king.
+    pub const fn promotion(kind: PieceKind, capture: bool) -> MoveFlag {
+        let base = match kind {
+            PieceKind::Knight => 0,
+            PieceKind::Bishop => 1,
+            PieceKind::Rook => 2,
+            PieceKind::Queen => 3,
+            _ => panic!("pawns can only promote to a knight, bishop, rook or queen"),
+        };
+        let capture = if capture { Self::CAPTURE_BIT } else { 0 };
+        Self::from_bits(Self::PROMOTION_BIT | capture | base)
+    }
+
+    const fn from_bits(bits: u8) -> MoveFlag {
+        use self::MoveFlag::*;
+        match bits & 0b1111 {
+            0 => Quiet,
+            1 => DoublePawnPush,
+            2 => KingCastle,
+            3 => QueenCastle,
+            4 => Capture,
+            5 => EnPassant,
+            8 => KnightPromotion,
+            9 => BishopPromotion,
+            10 => RookPromotion,
+            11 => QueenPromotion,
+            12 => KnightPromotionCapture,
+            13 => BishopPromotionCapture,
+            14 => RookPromotionCapture,
+            15 => QueenPromotionCapture,
+            // 6 and 7 are never constructed
+            _ => Quiet,
+        }
+    }
+}
+
+/// A move, packed into 16 bits.
+///
+/// Bits 0–5 hold the origin square, bits 6–11 the destination square and bits 12–15 the
+/// [`MoveFlag`]. Castling from the standard starting squares is encoded as the king's move,
+/// e.g. `e1g1`; any other Chess960 castling as the king capturing its own rook, e.g. `b1a1`,
+/// so no two legal moves share their UCI text.
+///
+/// A `Move` does not know which piece moves; it only makes sense together with the position
+/// it was generated for.
+///
+/// # Examples
+///
+/// ```
+/// use chess::repr::moves::{Move, MoveFlag};
+/// use chess::repr::board::square::Square;
+/// use chess::repr::piece::PieceKind;
+/// let promotion = Move::new(Square::E7, Square::E8, MoveFlag::QueenPromotion);
+/// assert_eq!(promotion.promotion(), Some(PieceKind::Queen));
+/// assert_eq!(promotion.to_string(), "e7e8q");
+/// assert_eq!("e7e8q".parse(), Ok(promotion));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Move(u16);
+
+impl Move {
+    /// The null move, which passes the turn. It is written `0000` in UCI.
+    pub const NULL: Move = Move(0);
+
+    /// Creates a move from `from` to `to`.
+    pub const fn new(from: Square, to: Square, flag: MoveFlag) -> Move {
+        Move((from as u16) | (to as u16) << 6 | (flag as u16) << 12)
+    }
+
+    /// The square the moving piece leaves.
+    pub const fn from(self) -> Square {
+        Square::ALL[(self.0 & 0b11_1111) as usize]
+    }
+
+    /// The square the moving piece arrives on.
+    pub const fn to(self) -> Square {
+        Square::ALL[(self.0 >> 6 & 0b11_1111) as usize]
+    }
+
+    /// The kind of move.
+    pub const fn flag(self) -> MoveFlag {
+        MoveFlag::from_bits((self.0 >> 12) as u8)
+    }
+
+    /// The raw 16 bits of the move.
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    /// Whether this is the null move.
+    pub const fn is_null(self) -> bool {
+        self.0 == Self::NULL.0
+    }
+
+    /// Whether the move captures a piece, including en passant.
+    pub const fn is_capture(self) -> bool {
+        self.flag() as u8 & MoveFlag::CAPTURE_BIT != 0
+    }
+
+    /// Whether the move promotes a pawn.
+    pub const fn is_promotion(self) -> bool {
+        self.flag() as u8 & MoveFlag::PROMOTION_BIT != 0
+    }
+
+    /// Whether the move is castling, to either side.
+    pub const fn is_castle(self) -> bool {
+        matches!(self.flag(), MoveFlag::KingCastle | MoveFlag::QueenCastle)
+    }
+
+    /// Whether the move is an en passant capture.
+    pub const fn is_en_passant(self) -> bool {
+        matches!(self.flag(), MoveFlag::EnPassant)
+    }
+
+    /// Whether the move is a pawn advancing two squares.
+    pub const fn is_double_pawn_push(self) -> bool {
+        matches!(self.flag(), MoveFlag::DoublePawnPush)
+    }
+
+    /// The piece a pawn promotes to, if the move is a promotion.
+    pub const fn promotion(self) -> Option<PieceKind> {
+        if !self.is_promotion() {
+            return None;
+        }
+        Some(match self.flag() as u8 & 0b11 {
+            0 => PieceKind::Knight,
+            1 => PieceKind::Bishop,
+            2 => PieceKind::Rook,
+            _ => PieceKind::Queen,
+        })
+    }
+}
+
+impl fmt::Display for Move {
+    /// Writes the move in UCI long algebraic notation, e.g. `e2e4` or `e7e8q`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_null() {
+            return write!(f, "0000");
+        }
+        write!(f, "{}{}", self.from(), self.to())?;
+        if let Some(kind) = self.promotion() {
+            write!(f, "{kind}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Move {
+    type Err = ParseMoveError;
+
+    /// Parse a move from UCI long algebraic notation.
+    ///
+    /// The text alone does not say whether a move captures, castles or advances a pawn two
+    /// squares, so the result is flagged [`MoveFlag::Quiet`] or as a non-capturing
+    /// promotion. Compare origin, destination and promotion against generated moves to
+    /// recover the full move.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "0000" {
+            return Ok(Move::NULL);
+        }
+        if let Some(c) = s.chars().find(|c| !c.is_ascii()) {
+            return Err(ParseMoveError::NonAscii(c));
+        }
+        if !(4..=5).contains(&s.len()) {
+            return Err(ParseMoveError::BadLength(s.len()));
+        }
+        let from: Square = s[0..2].parse()?;
+        let to: Square = s[2..4].parse()?;
+        let flag = match s[4..].chars().next() {
+            None => MoveFlag::Quiet,
+            Some(c) => match PieceKind::try_from(c) {
+                Ok(
+                    kind @ (PieceKind::Knight
+                    | PieceKind::Bishop
+                    | PieceKind::Rook
+                    | PieceKind::Queen),
+                ) if c.is_ascii_lowercase() => MoveFlag::promotion(kind, false),
+                _ => return Err(ParseMoveError::InvalidPromotion(c)),
+            },
+        };
+        Ok(Move::new(from, to, flag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_round_trip() {
+        for flag in [
+            MoveFlag::Quiet,
+            MoveFlag::DoublePawnPush,
+            MoveFlag::KingCastle,
+            MoveFlag::QueenCastle,
+            MoveFlag::Capture,
+            MoveFlag::EnPassant,
+            MoveFlag::KnightPromotion,
+            MoveFlag::QueenPromotionCapture,
+        ] {
+            for (from, to) in [(Square::A1, Square::H8), (Square::H8, Square::A1)] {
+                let mv = Move::new(from, to, flag);
+                assert_eq!((mv.from(), mv.to(), mv.flag()), (from, to, flag));
+            }
+        }
+    }
+
+    #[test]
+    fn flag_predicates() {
+        let ep = Move::new(Square::E5, Square::D6, MoveFlag::EnPassant);
+        assert!(ep.is_capture() && ep.is_en_passant() && !ep.is_promotion());
+        let promo = Move::new(Square::B2, Square::A1, MoveFlag::RookPromotionCapture);
+        assert!(promo.is_capture() && promo.is_promotion());
+        assert_eq!(promo.promotion(), Some(PieceKind::Rook));
+        let castle = Move::new(Square::E8, Square::C8, MoveFlag::QueenCastle);
+        assert!(castle.is_castle() && !castle.is_capture());
+        assert_eq!(
+            MoveFlag::promotion(PieceKind::Bishop, true),
+            MoveFlag::BishopPromotionCapture
+        );
+    }
+
+    #[test]
+    fn uci_round_trip() {
+        for uci in ["e2e4", "a7a8n", "h2h1b", "g7f8r", "b7b8q", "0000"] {
+            assert_eq!(uci.parse::<Move>().unwrap().to_string(), uci);
+        }
+        assert!(Move::NULL.is_null());
+    }
+
+    #[test]
+    fn uci_errors() {
+        assert_eq!("e2".parse::<Move>(), Err(ParseMoveError::BadLength(2)));
+        assert_eq!("e2e4qq".parse::<Move>(), Err(ParseMoveError::BadLength(6)));
+        assert_eq!("e2é4".parse::<Move>(), Err(ParseMoveError::NonAscii('é')));
+        assert_eq!("e2e4é".parse::<Move>(), Err(ParseMoveError::NonAscii('é')));
+        assert_eq!(
+            "i2e4".parse::<Move>(),
+            Err(ParseMoveError::InvalidSquare(
+                ParseSquareError::InvalidFile('i')
+            ))
+        );
+        assert_eq!(
+            "e7e8k".parse::<Move>(),
+            Err(ParseMoveError::InvalidPromotion('k'))
+        );
+        assert_eq!(
+            "e7e8Q".parse::<Move>(),
+            Err(ParseMoveError::InvalidPromotion('Q'))
+        );
+    }
+}
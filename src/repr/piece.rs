@@ -0,0 +1,387 @@
+//! Representation for the pieces and the sides that own them.
+
+use std::fmt;
+use std::fmt::Formatter;
+
+use thiserror::Error;
+
+/// An error raised when converting a character or an index into a [`Color`], [`PieceKind`]
+/// or [`Piece`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Error)]
+pub enum PieceError {
+    /// The character does not name a color.
+    #[error("invalid color {0:?}, expected one of `wb`")]
+    InvalidColor(char),
+    /// The character does not name a piece kind.
+    #[error("invalid piece kind {0:?}, expected one of `pnbrqk`")]
+    InvalidKind(char),
+    /// The character does not name a piece.
+    #[error("invalid piece {0:?}, expected one of `PNBRQKpnbrqk`")]
+    InvalidPiece(char),
+    /// The index is larger than the number of values of the type.
+    #[error("index {index} is out of range, expected less than {len}")]
+    IndexOutOfRange {
+        /// The offending index.
+        index: u8,
+        /// The number of values of the type.
+        len: u8,
+    },
+}
+
+/// One of the two sides of a chess game.
+///
+/// The [color] of a player is the color of the pieces they command.
+///
+/// [color]: https://en.wikipedia.org/wiki/Glossary_of_chess#color
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Color {
+    #[allow(missing_docs)]
+    White,
+    #[allow(missing_docs)]
+    Black,
+}
+
+impl Color {
+    /// Both colors, in index order.
+    pub const ALL: [Color; 2] = [Color::White, Color::Black];
+
+    /// The other side.
+    pub const fn opposite(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+
+    /// The index of this color, for use as an array key.
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl TryFrom<u8> for Color {
+    type Error = PieceError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Color::ALL
+            .get(value as usize)
+            .copied()
+            .ok_or(PieceError::IndexOutOfRange {
+                index: value,
+                len: Color::ALL.len() as u8,
+            })
+    }
+}
+
+impl From<Color> for u8 {
+    fn from(value: Color) -> Self {
+        value as u8
+    }
+}
+
+impl TryFrom<char> for Color {
+    type Error = PieceError;
+
+    /// Parse a color from its FEN side-to-move letter, `w` or `b`.
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            'w' => Ok(Color::White),
+            'b' => Ok(Color::Black),
+            _ => Err(PieceError::InvalidColor(value)),
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    /// Writes the FEN side-to-move letter, `w` or `b`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let c = match self {
+            Color::White => 'w',
+            Color::Black => 'b',
+        };
+        write!(f, "{c}")
+    }
+}
+
+/// The kind of a [piece], independent of its color.
+///
+/// [piece]: https://en.wikipedia.org/wiki/Glossary_of_chess#piece
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum PieceKind {
+    #[allow(missing_docs)]
+    Pawn,
+    #[allow(missing_docs)]
+    Knight,
+    #[allow(missing_docs)]
+    Bishop,
+    #[allow(missing_docs)]
+    Rook,
+    #[allow(missing_docs)]
+    Queen,
+    #[allow(missing_docs)]
+    King,
+}
+
+impl PieceKind {
+    /// Every piece kind, in index order.
+    pub const ALL: [PieceKind; 6] = [
+        PieceKind::Pawn,
+        PieceKind::Knight,
+        PieceKind::Bishop,
+        PieceKind::Rook,
+        PieceKind::Queen,
+        PieceKind::King,
+    ];
+
+    /// The index of this piece kind, for use as an array key.
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The lowercase letter of this piece kind, as used by FEN for Black's pieces.
+    pub const fn to_char(self) -> char {
+        match self {
+            PieceKind::Pawn => 'p',
+            PieceKind::Knight => 'n',
+            PieceKind::Bishop => 'b',
+            PieceKind::Rook => 'r',
+            PieceKind::Queen => 'q',
+            PieceKind::King => 'k',
+        }
+    }
+}
+
+impl TryFrom<u8> for PieceKind {
+    type Error = PieceError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        PieceKind::ALL
+            .get(value as usize)
+            .copied()
+            .ok_or(PieceError::IndexOutOfRange {
+                index: value,
+                len: PieceKind::ALL.len() as u8,
+            })
+    }
+}
+
+impl From<PieceKind> for u8 {
+    fn from(value: PieceKind) -> Self {
+        value as u8
+    }
+}
+
+impl TryFrom<char> for PieceKind {
+    type Error = PieceError;
+
+    /// Parse a piece kind from its letter, in either case.
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase() {
+            'p' => Ok(PieceKind::Pawn),
+            'n' => Ok(PieceKind::Knight),
+            'b' => Ok(PieceKind::Bishop),
+            'r' => Ok(PieceKind::Rook),
+            'q' => Ok(PieceKind::Queen),
+            'k' => Ok(PieceKind::King),
+            _ => Err(PieceError::InvalidKind(value)),
+        }
+    }
+}
+
+impl fmt::Display for PieceKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+/// A [piece] of a given [`Color`] and [`PieceKind`].
+///
+/// Pieces are indexed White first, in [`PieceKind`] order, so that a `[T; 12]` can be
+/// keyed by [`Piece::index`].
+///
+/// # Examples
+///
+/// ```
+/// use chess::repr::piece::{Color, Piece, PieceKind};
+/// let knight = Piece::try_from('n').unwrap();
+/// assert_eq!(knight, Piece::new(Color::Black, PieceKind::Knight));
+/// assert_eq!(knight.to_string(), "n");
+/// assert_eq!(knight.to_unicode(), '♞');
+/// ```
+///
+/// [piece]: https://en.wikipedia.org/wiki/Glossary_of_chess#piece
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Piece {
+    #[allow(missing_docs)]
+    WhitePawn,
+    #[allow(missing_docs)]
+    WhiteKnight,
+    #[allow(missing_docs)]
+    WhiteBishop,
+    #[allow(missing_docs)]
+    WhiteRook,
+    #[allow(missing_docs)]
+    WhiteQueen,
+    #[allow(missing_docs)]
+    WhiteKing,
+    #[allow(missing_docs)]
+    BlackPawn,
+    #[allow(missing_docs)]
+    BlackKnight,
+    #[allow(missing_docs)]
+    BlackBishop,
+    #[allow(missing_docs)]
+    BlackRook,
+    #[allow(missing_docs)]
+    BlackQueen,
+    #[allow(missing_docs)]
+    BlackKing,
+}
+
+impl Piece {
+    /// Every piece, in index order.
+    pub const ALL: [Piece; 12] = [
+        Piece::WhitePawn,
+        Piece::WhiteKnight,
+        Piece::WhiteBishop,
+        Piece::WhiteRook,
+        Piece::WhiteQueen,
+        Piece::WhiteKing,
+        Piece::BlackPawn,
+        Piece::BlackKnight,
+        Piece::BlackBishop,
+        Piece::BlackRook,
+        Piece::BlackQueen,
+        Piece::BlackKing,
+    ];
+
+    /// The piece of the given color and kind.
+    pub const fn new(color: Color, kind: PieceKind) -> Piece {
+        Piece::ALL[color.index() * PieceKind::ALL.len() + kind.index()]
+    }
+
+    /// The color of this piece.
+    pub const fn color(self) -> Color {
+        if (self as usize) < PieceKind::ALL.len() {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
+
+    /// The kind of this piece.
+    pub const fn kind(self) -> PieceKind {
+        PieceKind::ALL[self as usize % PieceKind::ALL.len()]
+    }
+
+    /// The index of this piece, for use as an array key.
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The FEN letter of this piece: uppercase for White, lowercase for Black.
+    pub const fn to_char(self) -> char {
+        let c = self.kind().to_char();
+        match self.color() {
+            Color::White => c.to_ascii_uppercase(),
+            Color::Black => c,
+        }
+    }
+
+    /// The Unicode chess figurine of this piece, e.g. `♘` for a white knight.
+    pub const fn to_unicode(self) -> char {
+        match self {
+            Piece::WhitePawn => '♙',
+            Piece::WhiteKnight => '♘',
+            Piece::WhiteBishop => '♗',
+            Piece::WhiteRook => '♖',
+            Piece::WhiteQueen => '♕',
+            Piece::WhiteKing => '♔',
+            Piece::BlackPawn => '♟',
+            Piece::BlackKnight => '♞',
+            Piece::BlackBishop => '♝',
+            Piece::BlackRook => '♜',
+            Piece::BlackQueen => '♛',
+            Piece::BlackKing => '♚',
+        }
+    }
+}
+
+impl TryFrom<u8> for Piece {
+    type Error = PieceError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Piece::ALL
+            .get(value as usize)
+            .copied()
+            .ok_or(PieceError::IndexOutOfRange {
+                index: value,
+                len: Piece::ALL.len() as u8,
+            })
+    }
+}
+
+impl From<Piece> for u8 {
+    fn from(value: Piece) -> Self {
+        value as u8
+    }
+}
+
+impl TryFrom<char> for Piece {
+    type Error = PieceError;
+
+    /// Parse a piece from its FEN letter: uppercase for White, lowercase for Black.
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        let kind = PieceKind::try_from(value).map_err(|_| PieceError::InvalidPiece(value))?;
+        let color = if value.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+        Ok(Piece::new(color, kind))
+    }
+}
+
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fen_letters_round_trip() {
+        let letters: String = Piece::ALL.iter().map(|p| p.to_char()).collect();
+        assert_eq!(letters, "PNBRQKpnbrqk");
+        for c in letters.chars() {
+            assert_eq!(Piece::try_from(c).unwrap().to_string(), c.to_string());
+        }
+    }
+
+    #[test]
+    fn invalid_characters_are_rejected() {
+        assert_eq!(Piece::try_from('x'), Err(PieceError::InvalidPiece('x')));
+        assert_eq!(Piece::try_from('♞'), Err(PieceError::InvalidPiece('♞')));
+        assert_eq!(PieceKind::try_from('1'), Err(PieceError::InvalidKind('1')));
+        assert_eq!(Color::try_from('W'), Err(PieceError::InvalidColor('W')));
+    }
+
+    #[test]
+    fn index_round_trip() {
+        for piece in Piece::ALL {
+            assert_eq!(Piece::try_from(u8::from(piece)), Ok(piece));
+            assert_eq!(Piece::new(piece.color(), piece.kind()), piece);
+        }
+        assert_eq!(
+            Piece::try_from(12),
+            Err(PieceError::IndexOutOfRange { index: 12, len: 12 })
+        );
+        assert_eq!(Color::White.opposite().opposite(), Color::White);
+    }
+}
@@ -0,0 +1,182 @@
+//! A complete chess position.
+//!
+//! A [Position] is everything needed to continue a game from a given point: the [Board], the
+//! side to move, the castling rights, the en passant target and the move counters. It is
+//! exchanged with other tools as [FEN].
+//!
+//! [FEN]: https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation
+mod fen;
+mod make_move;
+mod san;
+
+use crate::attacks::{
+    between, bishop_attacks, king_attacks, knight_attacks, pawn_attacks, rook_attacks,
+};
+use crate::movegen::{generate_legal, MoveList};
+use crate::repr::board::square::Square;
+use crate::repr::board::{BitBoard, Board};
+use crate::repr::castling::CastlingRights;
+use crate::repr::piece::{Color, PieceKind};
+
+pub use fen::FenError;
+pub use make_move::UndoInfo;
+pub use san::SanError;
+
+/// A chess position.
+///
+/// # Examples
+///
+/// ```
+/// use chess::repr::position::Position;
+/// use chess::repr::piece::Color;
+/// let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+/// let position: Position = fen.parse().unwrap();
+/// assert_eq!(position.side_to_move(), Color::Black);
+/// assert_eq!(position.to_string(), fen);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Position {
+    board: Board,
+    side_to_move: Color,
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    hash: u64,
+}
+
+impl Position {
+    /// The FEN of the standard starting position.
+    pub const STARTING_FEN: &'static str =
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    /// The standard starting position.
+    pub fn startpos() -> Position {
+        Self::STARTING_FEN
+            .parse()
+            .expect("the starting FEN is valid")
+    }
+
+    /// The placement of the pieces.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The side whose turn it is.
+    pub fn side_to_move(&self) -> Color {
+        self.side_to_move
+    }
+
+    /// The castling moves each side may still make.
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+
+    /// The square a pawn that just advanced two squares passed over, if any.
+    pub fn en_passant(&self) -> Option<Square> {
+        self.en_passant
+    }
+
+    /// The number of halfmoves since the last capture or pawn advance.
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    /// The number of the full move, starting at 1 and incremented after Black's move.
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    /// The Zobrist hash of the position, compatible with Polyglot opening books.
+    ///
+    /// Positions that differ only in their move counters hash equally. See
+    /// [`zobrist`](crate::repr::zobrist) for the keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chess::repr::position::Position;
+    /// assert_eq!(Position::startpos().zobrist_hash(), 0x463b_9618_1691_fc9c);
+    /// ```
+    pub fn zobrist_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// The pieces of either color that attack `square`, as if the board were `occupied`.
+    ///
+    /// Passing an occupancy other than the board's own lets callers look through pieces,
+    /// e.g. a king that is about to step away from a slider.
+    pub fn attackers_to(&self, square: Square, occupied: BitBoard) -> BitBoard {
+        let board = &self.board;
+        let rooks = board.pieces(PieceKind::Rook) | board.pieces(PieceKind::Queen);
+        let bishops = board.pieces(PieceKind::Bishop) | board.pieces(PieceKind::Queen);
+        (pawn_attacks(Color::White, square) & board.colored_pieces(Color::Black, PieceKind::Pawn))
+            | (pawn_attacks(Color::Black, square)
+                & board.colored_pieces(Color::White, PieceKind::Pawn))
+            | (knight_attacks(square) & board.pieces(PieceKind::Knight))
+            | (king_attacks(square) & board.pieces(PieceKind::King))
+            | (rook_attacks(square, occupied) & rooks)
+            | (bishop_attacks(square, occupied) & bishops)
+    }
+
+    /// Whether any piece of color `by` attacks `square`.
+    pub fn is_attacked(&self, square: Square, by: Color) -> bool {
+        !(self.attackers_to(square, self.board.occupied()) & self.board.color(by)).is_empty()
+    }
+
+    /// The enemy pieces giving check to the king of the side to move.
+    pub fn checkers(&self) -> BitBoard {
+        match self.board.king(self.side_to_move) {
+            Some(king) => {
+                self.attackers_to(king, self.board.occupied())
+                    & self.board.color(self.side_to_move.opposite())
+            }
+            None => BitBoard::EMPTY,
+        }
+    }
+
+    /// The pieces of the side to move that are pinned to their king by an enemy slider.
+    ///
+    /// A pinned piece may only move along the line between its king and the pinner.
+    pub fn pinned(&self) -> BitBoard {
+        let us = self.side_to_move;
+        let Some(king) = self.board.king(us) else {
+            return BitBoard::EMPTY;
+        };
+        let board = &self.board;
+        let them = board.color(us.opposite());
+        let rooks = board.pieces(PieceKind::Rook) | board.pieces(PieceKind::Queen);
+        let bishops = board.pieces(PieceKind::Bishop) | board.pieces(PieceKind::Queen);
+        let snipers = ((rook_attacks(king, BitBoard::EMPTY) & rooks)
+            | (bishop_attacks(king, BitBoard::EMPTY) & bishops))
+            & them;
+
+        let mut pinned = BitBoard::EMPTY;
+        for sniper in snipers {
+            let blockers = between(king, sniper) & board.occupied();
+            if blockers.population_count() == 1 {
+                pinned |= blockers & board.color(us);
+            }
+        }
+        pinned
+    }
+
+    /// The legal moves of the side to move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chess::repr::position::Position;
+    /// assert_eq!(Position::startpos().legal_moves().len(), 20);
+    /// ```
+    pub fn legal_moves(&self) -> MoveList {
+        let mut moves = MoveList::new();
+        generate_legal(self, &mut moves);
+        moves
+    }
+
+    /// Whether the side to move is in check.
+    pub fn in_check(&self) -> bool {
+        !self.checkers().is_empty()
+    }
+}
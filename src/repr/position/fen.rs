@@ -0,0 +1,497 @@
+//! Reading and writing [Position]s as FEN.
+use std::fmt;
+use std::fmt::Formatter;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use super::Position;
+use crate::repr::board::masks;
+use crate::repr::board::square::{File, ParseSquareError, Rank, Square};
+use crate::repr::board::Board;
+use crate::repr::castling::{outermost_rook, CastlingRights, CastlingSide};
+use crate::repr::piece::{Color, Piece, PieceKind};
+use crate::repr::zobrist;
+
+/// An error raised when parsing a [Position] from FEN.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum FenError {
+    /// The FEN does not have between 4 and 6 space-separated fields.
+    #[error("expected 4 to 6 space-separated fields, found {0}")]
+    FieldCount(usize),
+    /// The piece placement does not have 8 `/`-separated ranks.
+    #[error("piece placement: expected 8 ranks, found {0}")]
+    RankCount(usize),
+    /// The piece placement contains a character that is neither a piece nor a digit 1–8.
+    #[error("piece placement: invalid character {character:?} on rank {rank}")]
+    InvalidPiece {
+        /// The rank, 1–8, the character was found on.
+        rank: u8,
+        /// The offending character.
+        character: char,
+    },
+    /// A rank of the piece placement does not describe exactly 8 squares.
+    #[error("piece placement: rank {rank} describes {squares} squares, expected 8")]
+    RankLength {
+        /// The rank, 1–8, that has the wrong length.
+        rank: u8,
+        /// The number of squares the rank describes.
+        squares: u8,
+    },
+    /// The piece placement does not give a side exactly one king.
+    #[error("piece placement: {color:?} has {count} kings, expected 1")]
+    KingCount {
+        /// The side with the wrong number of kings.
+        color: Color,
+        /// The number of kings it has.
+        count: u8,
+    },
+    /// The piece placement puts a pawn on the first or eighth rank.
+    #[error("piece placement: pawn on {0}, which pawns cannot reach")]
+    PawnOnBackRank(Square),
+    /// The piece placement gives a side more than 8 pawns, or more promoted pieces than it is
+    /// missing pawns.
+    #[error("piece placement: {0:?} has more material than a game can reach")]
+    ImpossibleMaterial(Color),
+    /// The side to move is neither `w` nor `b`.
+    #[error("side to move: expected `w` or `b`, found {0:?}")]
+    InvalidSideToMove(String),
+    /// The castling rights contain a character other than `KQkq` or a file letter, `-`
+    /// alongside others, or one of `KQkq` with no rook on that side of a king on its back rank.
+    #[error("castling rights: invalid character {0:?}")]
+    InvalidCastling(char),
+    /// The castling rights name the same right twice.
+    #[error("castling rights: repeated character {0:?}")]
+    RepeatedCastling(char),
+    /// The en passant field is neither `-` nor a square.
+    #[error("en passant: invalid square {value:?}")]
+    InvalidEnPassant {
+        /// The offending field.
+        value: String,
+        /// The reason the field could not be parsed.
+        #[source]
+        source: ParseSquareError,
+    },
+    /// The en passant square is not an empty square right behind a pawn of the side that
+    /// just moved.
+    #[error(
+        "en passant: {0} is not an empty square a pawn of the side that just moved passed over"
+    )]
+    ImpossibleEnPassant(Square),
+    /// The halfmove clock is not a non-negative integer.
+    #[error("halfmove clock: invalid number {value:?}")]
+    InvalidHalfmoveClock {
+        /// The offending field.
+        value: String,
+        /// The reason the field could not be parsed.
+        #[source]
+        source: ParseIntError,
+    },
+    /// The fullmove number is not a non-negative integer.
+    #[error("fullmove number: invalid number {value:?}")]
+    InvalidFullmoveNumber {
+        /// The offending field.
+        value: String,
+        /// The reason the field could not be parsed.
+        #[source]
+        source: ParseIntError,
+    },
+}
+
+impl FromStr for Position {
+    type Err = FenError;
+
+    /// Parse a position from FEN.
+    ///
+    /// The halfmove clock and fullmove number may be omitted, as they are in EPD, in which
+    /// case they default to `0` and `1`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_ascii_whitespace().collect();
+        if !(4..=6).contains(&fields.len()) {
+            return Err(FenError::FieldCount(fields.len()));
+        }
+
+        let board = parse_placement(fields[0])?;
+        check_material(&board)?;
+        let side_to_move = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            other => return Err(FenError::InvalidSideToMove(other.to_owned())),
+        };
+        let castling_rights = parse_castling(fields[2], &board)?;
+        let en_passant = parse_en_passant(fields[3], side_to_move, &board)?;
+        let halfmove_clock = match fields.get(4) {
+            Some(value) => value
+                .parse()
+                .map_err(|source| FenError::InvalidHalfmoveClock {
+                    value: value.to_string(),
+                    source,
+                })?,
+            None => 0,
+        };
+        let fullmove_number = match fields.get(5) {
+            Some(value) => value
+                .parse()
+                .map_err(|source| FenError::InvalidFullmoveNumber {
+                    value: value.to_string(),
+                    source,
+                })?,
+            None => 1,
+        };
+
+        let mut position = Position {
+            board,
+            side_to_move,
+            castling_rights,
+            en_passant,
+            halfmove_clock,
+            fullmove_number,
+            hash: 0,
+        };
+        position.hash = zobrist::hash(&position);
+        Ok(position)
+    }
+}
+
+fn square(file: u8, rank: u8) -> Square {
+    Square::new(File::ALL[file as usize], Rank::ALL[rank as usize])
+}
+
+fn parse_placement(field: &str) -> Result<Board, FenError> {
+    let ranks: Vec<&str> = field.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(FenError::RankCount(ranks.len()));
+    }
+
+    let mut board = Board::default();
+    for (rank, row) in (0u8..8).rev().zip(ranks) {
+        let mut file = 0u8;
+        for character in row.chars() {
+            match character {
+                '1'..='8' => file = file.saturating_add(character as u8 - b'0'),
+                _ => {
+                    let piece = Piece::try_from(character).map_err(|_| FenError::InvalidPiece {
+                        rank: rank + 1,
+                        character,
+                    })?;
+                    if file < 8 {
+                        board.put(square(file, rank), piece);
+                    }
+                    file = file.saturating_add(1);
+                }
+            }
+        }
+        if file != 8 {
+            return Err(FenError::RankLength {
+                rank: rank + 1,
+                squares: file,
+            });
+        }
+    }
+    Ok(board)
+}
+
+/// Rejects material no game can reach: a side without exactly one king, a pawn on the first
+/// or eighth rank, or more pieces than a side's pawns could have promoted to.
+fn check_material(board: &Board) -> Result<(), FenError> {
+    let back_ranks = masks::RANK_1 | masks::RANK_8;
+    if let Some(square) = (board.pieces(PieceKind::Pawn) & back_ranks)
+        .into_iter()
+        .next()
+    {
+        return Err(FenError::PawnOnBackRank(square));
+    }
+    for color in Color::ALL {
+        let count = |kind| board.colored_pieces(color, kind).population_count();
+        let kings = count(PieceKind::King);
+        if kings != 1 {
+            return Err(FenError::KingCount {
+                color,
+                count: kings,
+            });
+        }
+        // every piece beyond the starting set must have been a pawn
+        let promoted = count(PieceKind::Queen).saturating_sub(1)
+            + count(PieceKind::Rook).saturating_sub(2)
+            + count(PieceKind::Bishop).saturating_sub(2)
+            + count(PieceKind::Knight).saturating_sub(2);
+        if count(PieceKind::Pawn) + promoted > 8 {
+            return Err(FenError::ImpossibleMaterial(color));
+        }
+    }
+    Ok(())
+}
+
+/// Parses standard, Shredder-FEN and X-FEN castling rights.
+///
+/// `KQkq` name the outermost rook on that side of a king on its back rank, and are rejected
+/// if there is none; file letters name the rook on that file.
+fn parse_castling(field: &str, board: &Board) -> Result<CastlingRights, FenError> {
+    let mut rights = CastlingRights::NONE;
+    if field == "-" {
+        return Ok(rights);
+    }
+    for c in field.chars() {
+        let color = if c.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let back_rank = CastlingSide::KingSide.king_destination(color).rank();
+        let king = board.king(color).filter(|king| king.rank() == back_rank);
+        let rook = |side| {
+            king.and_then(|king| outermost_rook(board, color, side, king))
+                .ok_or(FenError::InvalidCastling(c))
+        };
+        let file = match c.to_ascii_lowercase() {
+            'k' => rook(CastlingSide::KingSide)?,
+            'q' => rook(CastlingSide::QueenSide)?,
+            'a'..='h' => File::try_from(c).map_err(|_| FenError::InvalidCastling(c))?,
+            _ => return Err(FenError::InvalidCastling(c)),
+        };
+        let right = CastlingRights::rook(color, file);
+        if rights.contains(right) {
+            return Err(FenError::RepeatedCastling(c));
+        }
+        rights.insert(right);
+    }
+    Ok(rights)
+}
+
+fn parse_en_passant(
+    field: &str,
+    side_to_move: Color,
+    board: &Board,
+) -> Result<Option<Square>, FenError> {
+    if field == "-" {
+        return Ok(None);
+    }
+    let square: Square = field.parse().map_err(|source| FenError::InvalidEnPassant {
+        value: field.to_owned(),
+        source,
+    })?;
+    let (expected, pawn_rank) = match side_to_move {
+        Color::White => (Rank::Six, Rank::Five),
+        Color::Black => (Rank::Three, Rank::Four),
+    };
+    let pawn = Piece::new(side_to_move.opposite(), PieceKind::Pawn);
+    if square.rank() != expected
+        || board.piece_at(square).is_some()
+        || board.piece_at(Square::new(square.file(), pawn_rank)) != Some(pawn)
+    {
+        return Err(FenError::ImpossibleEnPassant(square));
+    }
+    Ok(Some(square))
+}
+
+impl fmt::Display for Position {
+    /// Writes the position as FEN.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for rank in (0u8..8).rev() {
+            let mut empty = 0;
+            for file in 0u8..8 {
+                match self.board.piece_at(square(file, rank)) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            write!(f, "{empty}")?;
+                            empty = 0;
+                        }
+                        write!(f, "{piece}")?;
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                write!(f, "{empty}")?;
+            }
+            if rank > 0 {
+                write!(f, "/")?;
+            }
+        }
+        write!(
+            f,
+            " {} {} ",
+            self.side_to_move,
+            self.castling_rights.to_xfen(&self.board)
+        )?;
+        match self.en_passant {
+            Some(square) => write!(f, "{square}")?,
+            None => write!(f, "-")?,
+        }
+        write!(f, " {} {}", self.halfmove_clock, self.fullmove_number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for fen in [
+            Position::STARTING_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 17 42",
+        ] {
+            assert_eq!(fen.parse::<Position>().unwrap().to_string(), fen);
+        }
+    }
+
+    #[test]
+    fn chess960_castling_notations() {
+        let xfen = "bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w KQkq - 0 1";
+        let shredder: Position = "bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w GEge - 0 1"
+            .parse()
+            .unwrap();
+        assert_eq!(shredder.to_string(), xfen);
+        assert_eq!(shredder, xfen.parse().unwrap());
+        assert_eq!(shredder.castling_rights().to_shredder(), "GEge");
+
+        // a second rook on the same side needs its file named in X-FEN
+        let inner = "rr2k3/8/8/8/8/8/8/4K1RR w Gb - 0 1";
+        let position: Position = inner.parse().unwrap();
+        assert_eq!(position.to_string(), inner);
+        assert_eq!(
+            "rr2k3/8/8/8/8/8/8/4K1RR w Kq - 0 1"
+                .parse::<Position>()
+                .unwrap()
+                .castling_rights()
+                .to_shredder(),
+            "Ha"
+        );
+    }
+
+    #[test]
+    fn missing_clocks_default() {
+        let position: Position = "8/8/8/8/8/8/8/K6k w - -".parse().unwrap();
+        assert_eq!(position.halfmove_clock(), 0);
+        assert_eq!(position.fullmove_number(), 1);
+    }
+
+    #[test]
+    fn errors_name_field_and_character() {
+        let err = |fen: &str| fen.parse::<Position>().unwrap_err();
+        assert_eq!(err("8/8/8/8 w - - 0 1"), FenError::RankCount(4));
+        assert_eq!(err("8/8/8/8/8/8/8/8 w"), FenError::FieldCount(2));
+        assert_eq!(
+            err("8/8/8/8/8/8/8/7x w - - 0 1"),
+            FenError::InvalidPiece {
+                rank: 1,
+                character: 'x'
+            }
+        );
+        assert_eq!(
+            err("8/8/8/8/8/8/8/8p w - - 0 1"),
+            FenError::RankLength {
+                rank: 1,
+                squares: 9
+            }
+        );
+        assert_eq!(
+            err("7/8/8/8/8/8/8/8 w - - 0 1"),
+            FenError::RankLength {
+                rank: 8,
+                squares: 7
+            }
+        );
+        assert_eq!(
+            err("4k3/8/8/8/8/8/8/4K3 x - - 0 1"),
+            FenError::InvalidSideToMove("x".to_owned())
+        );
+        assert_eq!(
+            err("4k3/8/8/8/8/8/8/4K2R w KX - 0 1"),
+            FenError::InvalidCastling('X')
+        );
+        assert_eq!(
+            err("4k3/8/8/8/8/8/8/4K2R w KK - 0 1"),
+            FenError::RepeatedCastling('K')
+        );
+        // `KQkq` need a rook on that side of a king on its back rank
+        assert_eq!(
+            err("4k3/8/8/8/8/8/8/4K3 w K - 0 1"),
+            FenError::InvalidCastling('K')
+        );
+        assert_eq!(
+            err("r3k3/8/8/8/8/8/8/4K2R w KQq - 0 1"),
+            FenError::InvalidCastling('Q')
+        );
+        assert_eq!(
+            err("4k3/8/8/8/8/8/4K3/R6R w K - 0 1"),
+            FenError::InvalidCastling('K')
+        );
+        assert_eq!(
+            err("4k3/8/8/8/8/8/8/4K3 w - e33 0 1"),
+            FenError::InvalidEnPassant {
+                value: "e33".to_owned(),
+                source: ParseSquareError::BadLength(3)
+            }
+        );
+        assert_eq!(
+            err("4k3/8/8/8/8/8/8/4K3 w - e3 0 1"),
+            FenError::ImpossibleEnPassant("e3".parse().unwrap())
+        );
+        assert_eq!(
+            err("QQQQQQQQ/QQ6/8/8/8/8/8/k6K w - - 0 1"),
+            FenError::ImpossibleMaterial(Color::White)
+        );
+        assert_eq!(
+            err("4k3/pppppppp/8/8/8/8/8/qq2K3 w - - 0 1"),
+            FenError::ImpossibleMaterial(Color::Black)
+        );
+        assert_eq!(
+            err("4k3/8/8/8/8/8/8/K3K3 w - - 0 1"),
+            FenError::KingCount {
+                color: Color::White,
+                count: 2
+            }
+        );
+        assert_eq!(
+            err("8/8/8/8/8/8/8/8 w - - 0 1"),
+            FenError::KingCount {
+                color: Color::White,
+                count: 0
+            }
+        );
+        assert_eq!(
+            err("8/8/8/8/8/8/8/4K3 w - - 0 1"),
+            FenError::KingCount {
+                color: Color::Black,
+                count: 0
+            }
+        );
+        assert_eq!(
+            err("P3k3/8/8/8/8/8/8/4K3 w - - 0 1"),
+            FenError::PawnOnBackRank(Square::A8)
+        );
+        assert_eq!(
+            err("4k3/8/8/8/8/8/8/4K2p w - - 0 1"),
+            FenError::PawnOnBackRank(Square::H1)
+        );
+        assert!("QQQQQQQQ/Q7/8/8/8/8/8/k6K w - - 0 1"
+            .parse::<Position>()
+            .is_ok());
+        // no pawn passed over e6, or the square is taken
+        assert_eq!(
+            err("4k3/8/8/3P4/8/8/8/4K3 w - e6 0 1"),
+            FenError::ImpossibleEnPassant("e6".parse().unwrap())
+        );
+        assert_eq!(
+            err("4k3/8/4n3/3Pp3/8/8/8/4K3 w - e6 0 1"),
+            FenError::ImpossibleEnPassant("e6".parse().unwrap())
+        );
+        assert!("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1"
+            .parse::<Position>()
+            .is_ok());
+        assert!(matches!(
+            err("4k3/8/8/8/8/8/8/4K3 w - - -1 1"),
+            FenError::InvalidHalfmoveClock { .. }
+        ));
+        assert!(matches!(
+            err("4k3/8/8/8/8/8/8/4K3 w - - 0 x"),
+            FenError::InvalidFullmoveNumber { .. }
+        ));
+    }
+}
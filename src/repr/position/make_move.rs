@@ -0,0 +1,312 @@
+//! Playing moves on a [Position] and taking them back.
+use super::Position;
+use crate::repr::board::square::{Rank, Square};
+use crate::repr::castling::{CastlingRights, CastlingSide};
+use crate::repr::moves::{Move, MoveFlag};
+use crate::repr::piece::{Color, Piece, PieceKind};
+use crate::repr::zobrist;
+
+/// The state a [Position] loses when a move is made, and needs back to unmake it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UndoInfo {
+    captured: Option<Piece>,
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    hash: u64,
+}
+
+impl UndoInfo {
+    /// The piece the move captured, if any.
+    pub fn captured(&self) -> Option<Piece> {
+        self.captured
+    }
+}
+
+/// The rights that are lost when a piece leaves or arrives on `square`: those of a rook
+/// starting there.
+fn rights_lost(square: Square) -> CastlingRights {
+    match square.rank() {
+        Rank::One => CastlingRights::rook(Color::White, square.file()),
+        Rank::Eight => CastlingRights::rook(Color::Black, square.file()),
+        _ => CastlingRights::NONE,
+    }
+}
+
+/// The side `flag` castles towards.
+fn castling_side(flag: MoveFlag) -> CastlingSide {
+    match flag {
+        MoveFlag::KingCastle => CastlingSide::KingSide,
+        _ => CastlingSide::QueenSide,
+    }
+}
+
+impl Position {
+    /// Plays `mv`, which must be legal in this position, and returns what is needed to take
+    /// it back with [`Position::unmake_move`].
+    ///
+    /// The null move passes the turn.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chess::repr::position::Position;
+    /// let mut position = Position::startpos();
+    /// let original = position;
+    /// let mv = position.legal_moves()[0];
+    /// let undo = position.make_move(mv);
+    /// assert_ne!(position, original);
+    /// position.unmake_move(mv, undo);
+    /// assert_eq!(position, original);
+    /// ```
+    pub fn make_move(&mut self, mv: Move) -> UndoInfo {
+        let mut undo = UndoInfo {
+            captured: None,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            hash: self.hash,
+        };
+        let us = self.side_to_move;
+        self.hash ^= zobrist::en_passant(self)
+            ^ zobrist::side_to_move(us)
+            ^ zobrist::side_to_move(us.opposite());
+        self.en_passant = None;
+        // FEN accepts any counters, so they saturate rather than overflow
+        self.halfmove_clock = self.halfmove_clock.saturating_add(1);
+        if us == Color::Black {
+            self.fullmove_number = self.fullmove_number.saturating_add(1);
+        }
+        self.side_to_move = us.opposite();
+        if mv.is_null() {
+            return undo;
+        }
+        // the castling key depends on where the king stands, so take it out before moving
+        self.hash ^= zobrist::castling(self.castling_rights, &self.board);
+
+        let (from, to) = (mv.from(), mv.to());
+        let piece = self
+            .board
+            .piece_at(from)
+            .expect("a legal move starts on an occupied square");
+        match mv.flag() {
+            MoveFlag::KingCastle | MoveFlag::QueenCastle => {
+                // in Chess960 either piece may land on the other's square, so lift both first
+                let side = castling_side(mv.flag());
+                let rook_from = self
+                    .castling_rights
+                    .rook_square(us, side, from)
+                    .expect("castling requires the right");
+                let (king_to, rook_to) = (side.king_destination(us), side.rook_destination(us));
+                let rook = Piece::new(us, PieceKind::Rook);
+                self.board.remove(from);
+                self.board.remove(rook_from);
+                self.board.put(king_to, piece);
+                self.board.put(rook_to, rook);
+                self.hash ^= zobrist::piece_square(piece, from)
+                    ^ zobrist::piece_square(piece, king_to)
+                    ^ zobrist::piece_square(rook, rook_from)
+                    ^ zobrist::piece_square(rook, rook_to);
+            }
+            MoveFlag::EnPassant => {
+                let captured_on = Square::new(to.file(), from.rank());
+                undo.captured = self.board.remove(captured_on);
+                self.board.move_piece(from, to);
+                self.hash ^= zobrist::piece_square(piece, from)
+                    ^ zobrist::piece_square(piece, to)
+                    ^ zobrist::piece_square(
+                        Piece::new(us.opposite(), PieceKind::Pawn),
+                        captured_on,
+                    );
+            }
+            flag => {
+                undo.captured = self.board.move_piece(from, to);
+                let placed = match mv.promotion() {
+                    Some(kind) => Piece::new(us, kind),
+                    None => piece,
+                };
+                if placed != piece {
+                    self.board.put(to, placed);
+                }
+                self.hash ^= zobrist::piece_square(piece, from) ^ zobrist::piece_square(placed, to);
+                if let Some(captured) = undo.captured {
+                    self.hash ^= zobrist::piece_square(captured, to);
+                }
+                if flag == MoveFlag::DoublePawnPush {
+                    self.en_passant = Some(Square::ALL[(from.index() + to.index()) / 2]);
+                }
+            }
+        }
+
+        if piece.kind() == PieceKind::Pawn || undo.captured.is_some() {
+            self.halfmove_clock = 0;
+        }
+        self.castling_rights
+            .remove(rights_lost(from).union(rights_lost(to)));
+        if piece.kind() == PieceKind::King {
+            self.castling_rights.remove(CastlingRights::color(us));
+        }
+        self.hash ^=
+            zobrist::castling(self.castling_rights, &self.board) ^ zobrist::en_passant(self);
+        undo
+    }
+
+    /// Takes back `mv`, which must be the last move made, restoring the position exactly as
+    /// it was before [`Position::make_move`] returned `undo`.
+    pub fn unmake_move(&mut self, mv: Move, undo: UndoInfo) {
+        let us = self.side_to_move.opposite();
+        self.side_to_move = us;
+        self.fullmove_number = undo.fullmove_number;
+        self.castling_rights = undo.castling_rights;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.hash = undo.hash;
+        if mv.is_null() {
+            return;
+        }
+
+        let (from, to) = (mv.from(), mv.to());
+        match mv.flag() {
+            MoveFlag::KingCastle | MoveFlag::QueenCastle => {
+                let side = castling_side(mv.flag());
+                let rook_from = undo
+                    .castling_rights
+                    .rook_square(us, side, from)
+                    .expect("castling requires the right");
+                let king = self.board.remove(side.king_destination(us));
+                let rook = self.board.remove(side.rook_destination(us));
+                if let (Some(king), Some(rook)) = (king, rook) {
+                    self.board.put(from, king);
+                    self.board.put(rook_from, rook);
+                }
+            }
+            MoveFlag::EnPassant => {
+                self.board.move_piece(to, from);
+                if let Some(captured) = undo.captured {
+                    self.board
+                        .put(Square::new(to.file(), from.rank()), captured);
+                }
+            }
+            _ => {
+                if mv.is_promotion() {
+                    self.board.put(to, Piece::new(us, PieceKind::Pawn));
+                }
+                self.board.move_piece(to, from);
+                if let Some(captured) = undo.captured {
+                    self.board.put(to, captured);
+                }
+            }
+        }
+    }
+
+    /// The position after playing `mv`, which must be legal in this position.
+    ///
+    /// This copies the position; use [`Position::make_move`] to update it in place.
+    pub fn play(&self, mv: Move) -> Position {
+        let mut position = *self;
+        position.make_move(mv);
+        position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRICKY_FENS: [&str; 7] = [
+        Position::STARTING_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    ];
+
+    #[test]
+    fn unmake_restores_every_legal_move_two_plies_deep() {
+        for fen in TRICKY_FENS {
+            let mut position: Position = fen.parse().unwrap();
+            let original = position;
+            for mv in position.legal_moves() {
+                let undo = position.make_move(mv);
+                let after = position;
+                assert_eq!(position, original.play(mv), "{fen} {mv}");
+                for reply in position.legal_moves() {
+                    let reply_undo = position.make_move(reply);
+                    position.unmake_move(reply, reply_undo);
+                    assert_eq!(position, after, "{fen} {mv} {reply}");
+                }
+                position.unmake_move(mv, undo);
+                assert_eq!(position, original, "{fen} {mv}");
+            }
+        }
+    }
+
+    fn play_uci(position: &Position, uci: &str) -> Position {
+        let mv = position
+            .legal_moves()
+            .into_iter()
+            .find(|mv| mv.to_string() == uci)
+            .unwrap();
+        position.play(mv)
+    }
+
+    #[test]
+    fn make_move_updates_state() {
+        let position = play_uci(&Position::startpos(), "e2e4");
+        assert_eq!(
+            position.to_string(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+        let position = play_uci(&position, "g8f6");
+        assert_eq!(
+            position.to_string(),
+            "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2"
+        );
+    }
+
+    #[test]
+    fn castling_moves_the_rook_and_clears_rights() {
+        let position: Position = TRICKY_FENS[1].parse().unwrap();
+        let position = play_uci(&position, "e1c1");
+        assert_eq!(
+            position.to_string(),
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/2KR3R b kq - 1 1"
+        );
+        let position = play_uci(&position, "h8h4");
+        assert_eq!(position.castling_rights().to_string(), "q");
+    }
+
+    #[test]
+    fn capturing_a_rook_clears_its_right() {
+        let position: Position = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".parse().unwrap();
+        let position = play_uci(&position, "a1a8");
+        assert_eq!(position.castling_rights().to_string(), "Kk");
+    }
+
+    #[test]
+    fn counters_saturate() {
+        let fen = "4k3/8/8/8/8/8/8/4K3 b - - 4294967295 4294967295";
+        let mut position: Position = fen.parse().unwrap();
+        let mv = position.legal_moves()[0];
+        let undo = position.make_move(mv);
+        assert_eq!(position.halfmove_clock(), u32::MAX);
+        assert_eq!(position.fullmove_number(), u32::MAX);
+        position.unmake_move(mv, undo);
+        assert_eq!(position.to_string(), fen);
+    }
+
+    #[test]
+    fn null_move_passes_the_turn() {
+        let mut position: Position = TRICKY_FENS[6].parse().unwrap();
+        let original = position;
+        let undo = position.make_move(Move::NULL);
+        assert_eq!(position.side_to_move(), Color::Black);
+        assert_eq!(position.en_passant(), None);
+        position.unmake_move(Move::NULL, undo);
+        assert_eq!(position, original);
+    }
+}
@@ -0,0 +1,329 @@
+//! Reading and writing moves of a [Position] in Standard Algebraic Notation.
+//!
+//! [SAN] names a move by the piece that moves and its destination, adding just enough of the
+//! origin to tell it apart from other legal moves, e.g. `Nbd7`, `exd8=Q+` or `O-O-O#`.
+//!
+//! [SAN]: https://en.wikipedia.org/wiki/Algebraic_notation_(chess)
+use thiserror::Error;
+
+use super::Position;
+use crate::repr::board::square::{File, Rank, Square};
+use crate::repr::moves::{Move, MoveFlag};
+use crate::repr::piece::PieceKind;
+
+/// An error raised when parsing a [Move] from SAN.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum SanError {
+    /// The text is not SAN.
+    #[error("invalid SAN {0:?}")]
+    Invalid(String),
+    /// No legal move matches the text.
+    #[error("illegal move {0:?}")]
+    Illegal(String),
+    /// Several legal moves match the text.
+    #[error("ambiguous move {0:?}")]
+    Ambiguous(String),
+}
+
+/// The parts of a non-castling SAN move.
+struct SanMove {
+    kind: PieceKind,
+    from_file: Option<File>,
+    from_rank: Option<Rank>,
+    to: Square,
+    promotion: Option<PieceKind>,
+}
+
+impl SanMove {
+    fn parse(text: &str) -> Option<SanMove> {
+        let mut chars: Vec<char> = text.chars().collect();
+
+        let mut promotion = None;
+        if let Some(&last) = chars.last() {
+            if chars.len() > 2 && "NBRQnbrq".contains(last) {
+                // the `=` is often left out, e.g. `e8Q`
+                let mut end = chars.len() - 1;
+                if chars[end - 1] == '=' {
+                    end -= 1;
+                }
+                if chars[end - 1].is_ascii_digit() {
+                    promotion = Some(PieceKind::try_from(last).ok()?);
+                    chars.truncate(end);
+                }
+            }
+        }
+
+        let kind = match chars.first() {
+            Some(&c @ ('N' | 'B' | 'R' | 'Q' | 'K')) => {
+                chars.remove(0);
+                PieceKind::try_from(c).ok()?
+            }
+            _ => PieceKind::Pawn,
+        };
+
+        if chars.len() < 2 {
+            return None;
+        }
+        let to_chars = chars.split_off(chars.len() - 2);
+        let to = Square::new(
+            File::try_from(to_chars[0]).ok()?,
+            Rank::try_from(to_chars[1]).ok()?,
+        );
+        if chars.last() == Some(&'x') {
+            chars.pop();
+        }
+
+        let (mut from_file, mut from_rank) = (None, None);
+        for c in chars {
+            match c {
+                'a'..='h' if from_file.is_none() && from_rank.is_none() => {
+                    from_file = Some(File::try_from(c).ok()?);
+                }
+                '1'..='8' if from_rank.is_none() => from_rank = Some(Rank::try_from(c).ok()?),
+                _ => return None,
+            }
+        }
+
+        Some(SanMove {
+            kind,
+            from_file,
+            from_rank,
+            to,
+            promotion,
+        })
+    }
+
+    fn matches(&self, position: &Position, mv: Move) -> bool {
+        let Some(piece) = position.board().piece_at(mv.from()) else {
+            return false;
+        };
+        // a pawn that captures always names the file it leaves
+        let from_file = match (self.kind, self.from_file) {
+            (PieceKind::Pawn, None) => Some(self.to.file()),
+            (_, file) => file,
+        };
+        !mv.is_castle()
+            && piece.kind() == self.kind
+            && mv.to() == self.to
+            && mv.promotion() == self.promotion
+            && from_file.is_none_or(|file| mv.from().file() == file)
+            && self.from_rank.is_none_or(|rank| mv.from().rank() == rank)
+    }
+}
+
+impl Position {
+    /// The SAN of `mv`, which must be legal in this position.
+    ///
+    /// The null move is written `--`, as in PGN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chess::repr::position::Position;
+    /// let position = Position::startpos();
+    /// let mv = position.parse_san("Nf3").unwrap();
+    /// assert_eq!(mv.to_string(), "g1f3");
+    /// assert_eq!(position.san(mv), "Nf3");
+    /// ```
+    pub fn san(&self, mv: Move) -> String {
+        if mv.is_null() {
+            return "--".to_owned();
+        }
+        let mut san = match mv.flag() {
+            MoveFlag::KingCastle => "O-O".to_owned(),
+            MoveFlag::QueenCastle => "O-O-O".to_owned(),
+            _ => self.san_without_suffix(mv),
+        };
+
+        let after = self.play(mv);
+        if after.in_check() {
+            san.push(if after.legal_moves().is_empty() {
+                '#'
+            } else {
+                '+'
+            });
+        }
+        san
+    }
+
+    fn san_without_suffix(&self, mv: Move) -> String {
+        let (from, to) = (mv.from(), mv.to());
+        let kind = self
+            .board
+            .piece_at(from)
+            .expect("a legal move starts on an occupied square")
+            .kind();
+
+        let mut san = String::new();
+        if kind == PieceKind::Pawn {
+            if mv.is_capture() {
+                san.push(from.file().to_char());
+            }
+        } else {
+            san.push(kind.to_char().to_ascii_uppercase());
+
+            let others: Vec<Square> = self
+                .legal_moves()
+                .into_iter()
+                .filter(|other| {
+                    other.to() == to
+                        && other.from() != from
+                        && !other.is_castle()
+                        && self.board.piece_at(other.from()).map(|piece| piece.kind()) == Some(kind)
+                })
+                .map(|other| other.from())
+                .collect();
+            if !others.is_empty() {
+                let file_unique = others.iter().all(|other| other.file() != from.file());
+                let rank_unique = others.iter().all(|other| other.rank() != from.rank());
+                if file_unique {
+                    san.push(from.file().to_char());
+                } else if rank_unique {
+                    san.push(from.rank().to_char());
+                } else {
+                    san.push_str(&from.to_string());
+                }
+            }
+        }
+        if mv.is_capture() {
+            san.push('x');
+        }
+        san.push_str(&to.to_string());
+        if let Some(promotion) = mv.promotion() {
+            san.push('=');
+            san.push(promotion.to_char().to_ascii_uppercase());
+        }
+        san
+    }
+
+    /// Parses a legal move of this position from SAN.
+    ///
+    /// Common variations are accepted: castling written with zeros, promotions without `=`,
+    /// and check, mate and annotation suffixes such as `+`, `#` or `!?`, which are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the text is not SAN, or if it matches no legal move or several.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chess::repr::position::{Position, SanError};
+    /// let position = Position::startpos();
+    /// assert_eq!(position.parse_san("e4!?").unwrap().to_string(), "e2e4");
+    /// assert_eq!(position.parse_san("e5"), Err(SanError::Illegal("e5".to_owned())));
+    /// ```
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanError> {
+        let text = san.trim().trim_end_matches(['+', '#', '!', '?']);
+        if text == "--" {
+            return Ok(Move::NULL);
+        }
+
+        let castle = match text {
+            "O-O" | "0-0" => Some(MoveFlag::KingCastle),
+            "O-O-O" | "0-0-0" => Some(MoveFlag::QueenCastle),
+            _ => None,
+        };
+        let legal = self.legal_moves();
+        let mut candidates: Vec<Move> = match castle {
+            Some(flag) => legal.into_iter().filter(|mv| mv.flag() == flag).collect(),
+            None => {
+                let parsed =
+                    SanMove::parse(text).ok_or_else(|| SanError::Invalid(san.to_owned()))?;
+                legal
+                    .into_iter()
+                    .filter(|&mv| parsed.matches(self, mv))
+                    .collect()
+            }
+        };
+
+        match candidates.len() {
+            0 => Err(SanError::Illegal(san.to_owned())),
+            1 => Ok(candidates.remove(0)),
+            _ => Err(SanError::Ambiguous(san.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn san(fen: &str, uci: &str) -> String {
+        let position: Position = fen.parse().unwrap();
+        let mv = position
+            .legal_moves()
+            .into_iter()
+            .find(|mv| mv.to_string() == uci)
+            .unwrap();
+        position.san(mv)
+    }
+
+    #[test]
+    fn renders_san() {
+        let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        assert_eq!(san(kiwipete, "e1c1"), "O-O-O");
+        assert_eq!(san(kiwipete, "e1g1"), "O-O");
+        assert_eq!(san(kiwipete, "d5e6"), "dxe6");
+        assert_eq!(san(kiwipete, "e5f7"), "Nxf7");
+        assert_eq!(san(kiwipete, "f3f6"), "Qxf6");
+        // both knights reach b5 from different files
+        assert_eq!(san(kiwipete, "c3b5"), "Nb5");
+        assert_eq!(san(kiwipete, "c3a4"), "Na4");
+        assert_eq!(san(kiwipete, "e5d3"), "Nd3");
+
+        // knights on the same file, then on the same file and rank as others
+        assert_eq!(san("4k3/8/8/3N4/8/3N4/8/4K3 w - - 0 1", "d5f4"), "N5f4");
+        assert_eq!(san("4k3/8/8/3N4/8/3N4/8/4K3 w - - 0 1", "d3b4"), "N3b4");
+        assert_eq!(san("4k3/8/8/3N4/8/3N4/8/4K3 w - - 0 1", "d3b2"), "Nb2");
+        assert_eq!(san("7k/8/8/1Q1Q4/8/1Q6/8/4K3 w - - 0 1", "b5c4"), "Qb5c4");
+
+        assert_eq!(san("3r2k1/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7d8q"), "exd8=Q+");
+        assert_eq!(san("6k1/5ppp/8/8/8/8/8/R3K3 w Q - 0 1", "e1c1"), "O-O-O");
+        assert_eq!(san("6k1/5ppp/8/8/8/8/8/R3K3 w Q - 0 1", "a1a8"), "Ra8#");
+        assert_eq!(Position::startpos().san(Move::NULL), "--");
+    }
+
+    #[test]
+    fn parses_common_variants() {
+        let position: Position = "4k3/1P6/8/8/8/8/8/R3K2R w KQ - 0 1".parse().unwrap();
+        let uci = |san: &str| position.parse_san(san).unwrap().to_string();
+        assert_eq!(uci("0-0"), "e1g1");
+        assert_eq!(uci("O-O-O+"), "e1c1");
+        assert_eq!(uci("b8Q"), "b7b8q");
+        assert_eq!(uci("b8=N!?"), "b7b8n");
+        assert_eq!(uci("Rh7"), "h1h7");
+        assert_eq!(uci("R1a4"), "a1a4");
+        assert_eq!(position.parse_san("--"), Ok(Move::NULL));
+    }
+
+    #[test]
+    fn rejects_bad_san() {
+        let position: Position = "4k3/4P3/8/8/R6R/8/8/4K3 w - - 0 1".parse().unwrap();
+        let err = |san: &str| position.parse_san(san).unwrap_err();
+        assert_eq!(err("Rd4"), SanError::Ambiguous("Rd4".to_owned()));
+        assert_eq!(err("e8"), SanError::Illegal("e8".to_owned()));
+        assert_eq!(err("Nf3"), SanError::Illegal("Nf3".to_owned()));
+        assert_eq!(err("Zz9"), SanError::Invalid("Zz9".to_owned()));
+        assert_eq!(err(""), SanError::Invalid("".to_owned()));
+    }
+
+    #[test]
+    fn round_trips_every_legal_move() {
+        for fen in [
+            Position::STARTING_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "7k/8/8/1Q1Q4/8/1Q6/8/4K3 w - - 0 1",
+            "bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w KQkq - 0 1",
+        ] {
+            let position: Position = fen.parse().unwrap();
+            for mv in position.legal_moves() {
+                let san = position.san(mv);
+                assert_eq!(position.parse_san(&san), Ok(mv), "{fen} {san}");
+            }
+        }
+    }
+}
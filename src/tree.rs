@@ -0,0 +1,543 @@
+//! A game together with its variations and annotations.
+//!
+//! A [GameTree] stores every move as a node of an arena, addressed by [NodeId]. Each node
+//! lists the moves that may follow it, the main line first, so variations can be reordered
+//! or removed without touching the rest of the tree.
+//!
+//! Trees convert to and from the [PGN model](crate::pgn::Game). Key/value annotations are
+//! written to PGN as `[%key value]` commands inside comments, as `[%clk]` is, and read back
+//! from them.
+use std::collections::BTreeMap;
+
+use crate::pgn::{Game, GameResult, MoveNode, Variation};
+use crate::repr::moves::Move;
+use crate::repr::position::{FenError, Position};
+
+/// The address of a node of a [GameTree].
+///
+/// Ids stay valid until their node is deleted, and are never reused by the same tree.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+/// A node of a [GameTree]: a move, the moves that may follow it and its annotations.
+///
+/// The root node stands for the starting position and has no move.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Node {
+    mv: Option<Move>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    /// Comments on the position after the move.
+    pub comments: Vec<String>,
+    /// Comments before the move, for a move that starts a variation.
+    pub starting_comments: Vec<String>,
+    /// The numeric annotation glyphs of the move.
+    pub nags: Vec<u8>,
+    /// Arbitrary key/value annotations, such as `clk` or `eval`.
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl Node {
+    /// The move leading to this node, or `None` for the root.
+    pub fn mv(&self) -> Option<Move> {
+        self.mv
+    }
+
+    /// The node this one follows, or `None` for the root.
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    /// The nodes that may follow this one, the main line first.
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+}
+
+/// A game tree: a starting position and every line played from it.
+///
+/// # Examples
+///
+/// ```
+/// use chess::repr::position::Position;
+/// use chess::tree::GameTree;
+/// let mut tree = GameTree::new(Position::startpos());
+/// let root = tree.root();
+/// let e4 = tree.add_move(root, Position::startpos().parse_san("e4").unwrap()).unwrap();
+/// let d4 = tree.add_move(root, Position::startpos().parse_san("d4").unwrap()).unwrap();
+/// assert_eq!(tree.node(root).unwrap().children(), [e4, d4]);
+///
+/// tree.promote_to_main(d4);
+/// assert_eq!(tree.mainline(), [d4]);
+/// let fen = tree.position(d4).unwrap().to_string();
+/// assert_eq!(fen, "rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 1");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameTree {
+    position: Position,
+    nodes: Vec<Option<Node>>,
+    /// The tag pairs of the game, in PGN order, without `SetUp` and `FEN`: the starting
+    /// position sets those.
+    pub tags: Vec<(String, String)>,
+    /// The result of the game.
+    pub result: GameResult,
+}
+
+impl GameTree {
+    /// A tree with no moves from `position`.
+    pub fn new(position: Position) -> GameTree {
+        GameTree {
+            position,
+            nodes: vec![Some(Node::default())],
+            tags: Vec::new(),
+            result: GameResult::Unknown,
+        }
+    }
+
+    /// The node of the starting position.
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    /// The starting position.
+    pub fn root_position(&self) -> &Position {
+        &self.position
+    }
+
+    /// Whether `id` is a node of this tree that has not been deleted.
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.node(id).is_some()
+    }
+
+    /// The node `id`, or `None` if it has been deleted.
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id.0)?.as_ref()
+    }
+
+    /// The node `id`, to edit its annotations, or `None` if it has been deleted.
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(id.0)?.as_mut()
+    }
+
+    /// The node `id`, reached from a node that has not been deleted.
+    fn live(&self, id: NodeId) -> &Node {
+        self.node(id).expect("the children of a live node are live")
+    }
+
+    /// Adds `mv` as the last variation after `parent`, or as its main line if it has none.
+    ///
+    /// If `parent` is already followed by `mv`, that node is returned instead. Returns `None`
+    /// if `parent` has been deleted or `mv` is not legal in its position.
+    pub fn add_move(&mut self, parent: NodeId, mv: Move) -> Option<NodeId> {
+        if !self.position(parent)?.legal_moves().contains(&mv) {
+            return None;
+        }
+        let children = &self.live(parent).children;
+        if let Some(&existing) = children
+            .iter()
+            .find(|&&child| self.live(child).mv == Some(mv))
+        {
+            return Some(existing);
+        }
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Some(Node {
+            mv: Some(mv),
+            parent: Some(parent),
+            ..Node::default()
+        }));
+        self.node_mut(parent)?.children.push(id);
+        Some(id)
+    }
+
+    /// The main line: the nodes reached by following the first child from the root.
+    pub fn mainline(&self) -> Vec<NodeId> {
+        let mut line = Vec::new();
+        let mut current = self.root();
+        while let Some(&next) = self.live(current).children.first() {
+            line.push(next);
+            current = next;
+        }
+        line
+    }
+
+    /// The moves leading from the root to `id`, or `None` if it has been deleted.
+    pub fn moves_to(&self, id: NodeId) -> Option<Vec<Move>> {
+        let mut moves = Vec::new();
+        let mut node = self.node(id)?;
+        while let (Some(mv), Some(parent)) = (node.mv, node.parent) {
+            moves.push(mv);
+            node = self.live(parent);
+        }
+        moves.reverse();
+        Some(moves)
+    }
+
+    /// The position after the move of `id`, or `None` if it has been deleted.
+    pub fn position(&self, id: NodeId) -> Option<Position> {
+        let mut position = self.position;
+        for mv in self.moves_to(id)? {
+            position.make_move(mv);
+        }
+        Some(position)
+    }
+
+    /// The siblings of `id`, including itself, and its index among them; `None` for the root
+    /// and deleted nodes.
+    fn siblings_mut(&mut self, id: NodeId) -> Option<(&mut Vec<NodeId>, usize)> {
+        let parent = self.node(id)?.parent?;
+        let siblings = &mut self.node_mut(parent)?.children;
+        let index = siblings.iter().position(|&child| child == id)?;
+        Some((siblings, index))
+    }
+
+    /// Moves the variation starting at `id` one place up among its siblings, returning
+    /// whether it moved; deleted nodes never move.
+    ///
+    /// Promoting the first variation after the main line makes it the main line.
+    pub fn promote(&mut self, id: NodeId) -> bool {
+        match self.siblings_mut(id) {
+            Some((siblings, index)) if index > 0 => {
+                siblings.swap(index - 1, index);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Makes the variation starting at `id` the main line after its parent, returning
+    /// whether it moved.
+    pub fn promote_to_main(&mut self, id: NodeId) -> bool {
+        match self.siblings_mut(id) {
+            Some((siblings, index)) if index > 0 => {
+                let node = siblings.remove(index);
+                siblings.insert(0, node);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Moves the variation starting at `id` one place down among its siblings, returning
+    /// whether it moved.
+    pub fn demote(&mut self, id: NodeId) -> bool {
+        match self.siblings_mut(id) {
+            Some((siblings, index)) if index + 1 < siblings.len() => {
+                siblings.swap(index, index + 1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Deletes `id` and every node after it, returning whether anything was deleted.
+    ///
+    /// The root cannot be deleted.
+    pub fn delete(&mut self, id: NodeId) -> bool {
+        match self.siblings_mut(id) {
+            Some((siblings, index)) => {
+                siblings.remove(index);
+            }
+            None => return false,
+        }
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            if let Some(node) = self.nodes[id.0].take() {
+                pending.extend(node.children);
+            }
+        }
+        true
+    }
+
+    /// The line starting at `start`, with the alternatives to every move of it that is the
+    /// main line after its parent.
+    fn variation(&self, start: NodeId) -> Variation {
+        let mut variation = Variation {
+            comments: self.live(start).starting_comments.clone(),
+            moves: Vec::new(),
+        };
+        let mut current = Some(start);
+        while let Some(id) = current {
+            let node = self.live(id);
+            let mut move_node = MoveNode::new(node.mv.expect("only the root has no move"));
+            move_node.nags.clone_from(&node.nags);
+            move_node.comments = export_comments(node);
+            let parent = self.live(node.parent.expect("only the root has no parent"));
+            if parent.children.first() == Some(&id) {
+                move_node.variations = parent.children[1..]
+                    .iter()
+                    .map(|&alternative| self.variation(alternative))
+                    .collect();
+            }
+            variation.moves.push(move_node);
+            current = node.children.first().copied();
+        }
+        variation
+    }
+
+    /// Adds the moves of `variation` after `parent`, with their variations.
+    fn add_variation(&mut self, parent: NodeId, variation: &Variation) {
+        let mut parent = parent;
+        for (i, move_node) in variation.moves.iter().enumerate() {
+            let id = self
+                .add_move(parent, move_node.mv)
+                .expect("the moves of a game are legal");
+            let (comments, annotations) = import_comments(&move_node.comments);
+            let node = self.node_mut(id).expect("the node was just added");
+            if i == 0 {
+                node.starting_comments
+                    .extend(variation.comments.iter().cloned());
+            }
+            node.nags.extend(&move_node.nags);
+            node.comments.extend(comments);
+            node.annotations.extend(annotations);
+            for alternative in &move_node.variations {
+                self.add_variation(parent, alternative);
+            }
+            parent = id;
+        }
+    }
+}
+
+/// The comments of `node` for PGN, its annotations first as `[%key value]` commands.
+fn export_comments(node: &Node) -> Vec<String> {
+    let mut comments = Vec::new();
+    if !node.annotations.is_empty() {
+        let commands: Vec<String> = node
+            .annotations
+            .iter()
+            .map(|(key, value)| format!("[%{key} {value}]"))
+            .collect();
+        comments.push(commands.join(" "));
+    }
+    comments.extend(node.comments.iter().cloned());
+    comments
+}
+
+/// Splits the `[%key value]` commands out of PGN `comments`, dropping comments that held
+/// nothing else.
+fn import_comments(comments: &[String]) -> (Vec<String>, Vec<(String, String)>) {
+    let mut texts = Vec::new();
+    let mut annotations = Vec::new();
+    for comment in comments {
+        let mut text = String::new();
+        let mut remaining = comment.as_str();
+        while let Some(start) = remaining.find("[%") {
+            let Some(end) = remaining[start..].find(']') else {
+                break;
+            };
+            let command = remaining[start + 2..start + end].trim();
+            let (key, value) = command
+                .split_once(char::is_whitespace)
+                .unwrap_or((command, ""));
+            annotations.push((key.to_owned(), value.trim().to_owned()));
+            text.push_str(&remaining[..start]);
+            remaining = &remaining[start + end + 1..];
+        }
+        if text.is_empty() && remaining.len() == comment.len() {
+            // no commands: keep the comment exactly as written
+            texts.push(comment.clone());
+            continue;
+        }
+        text.push_str(remaining);
+        let text = text.trim();
+        if !text.is_empty() {
+            texts.push(text.to_owned());
+        }
+    }
+    (texts, annotations)
+}
+
+impl TryFrom<&Game> for GameTree {
+    type Error = FenError;
+
+    /// The tree of `game`, starting from the position of its `FEN` tag, if any.
+    ///
+    /// # Panics
+    ///
+    /// If a move of `game` is not legal in the position it is played from.
+    fn try_from(game: &Game) -> Result<Self, Self::Error> {
+        let mut tree = GameTree::new(game.starting_position()?);
+        tree.tags = game
+            .tags
+            .iter()
+            .filter(|(name, _)| name != "SetUp" && name != "FEN")
+            .cloned()
+            .collect();
+        tree.result = game.result;
+        let (comments, annotations) = import_comments(&game.mainline.comments);
+        let root = tree
+            .node_mut(tree.root())
+            .expect("the root is never deleted");
+        root.comments = comments;
+        root.annotations.extend(annotations);
+        let mainline = Variation {
+            comments: Vec::new(),
+            moves: game.mainline.moves.clone(),
+        };
+        tree.add_variation(tree.root(), &mainline);
+        Ok(tree)
+    }
+}
+
+impl From<&GameTree> for Game {
+    /// The PGN model of `tree`; comments and annotations on the root come before the first
+    /// move.
+    fn from(tree: &GameTree) -> Self {
+        let root = tree.live(tree.root());
+        let mut mainline = match root.children.first() {
+            Some(&first) => tree.variation(first),
+            None => Variation::default(),
+        };
+        let mut comments = export_comments(root);
+        comments.append(&mut mainline.comments);
+        mainline.comments = comments;
+        let mut game = Game::from_position(&tree.position);
+        game.tags.extend(tree.tags.iter().cloned());
+        game.mainline = mainline;
+        game.result = tree.result;
+        game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgn::{PgnReader, PgnWriter};
+    use crate::repr::board::square::Square;
+    use crate::repr::moves::MoveFlag;
+
+    const PGN: &str = r#"[Event "Tree"]
+
+{[%eval 0.2] Start} 1. e4 {[%clk 0:03:00] [%eval 0.3] Best by test} e5 $1 (1... c5 {Sicilian} 2. Nf3
+(2. c3) d6) (1... e6) 2. Nf3 Nc6 1/2-1/2
+"#;
+
+    fn tree() -> GameTree {
+        let game = PgnReader::new(PGN.as_bytes()).next().unwrap().unwrap();
+        GameTree::try_from(&game).unwrap()
+    }
+
+    fn sans(tree: &GameTree, line: &[NodeId]) -> Vec<String> {
+        line.iter()
+            .map(|&id| {
+                let parent = tree.node(id).unwrap().parent().unwrap();
+                tree.position(parent)
+                    .unwrap()
+                    .san(tree.node(id).unwrap().mv().unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn imports_pgn() {
+        let tree = tree();
+        let mainline = tree.mainline();
+        assert_eq!(sans(&tree, &mainline), ["e4", "e5", "Nf3", "Nc6"]);
+        let root = tree.node(tree.root()).unwrap();
+        assert_eq!(root.comments, ["Start"]);
+        assert_eq!(root.annotations["eval"], "0.2");
+
+        let e4 = tree.node(mainline[0]).unwrap();
+        assert_eq!(e4.comments, ["Best by test"]);
+        assert_eq!(e4.annotations["clk"], "0:03:00");
+        assert_eq!(e4.annotations["eval"], "0.3");
+        assert_eq!(sans(&tree, e4.children()), ["e5", "c5", "e6"]);
+
+        let c5 = tree.node(e4.children()[1]).unwrap();
+        assert_eq!(c5.comments, ["Sicilian"]);
+        assert_eq!(sans(&tree, c5.children()), ["Nf3", "c3"]);
+        assert_eq!(tree.result, GameResult::Draw);
+    }
+
+    #[test]
+    fn round_trips_through_pgn() {
+        let tree = tree();
+        let game = Game::from(&tree);
+        assert_eq!(GameTree::try_from(&game).unwrap(), tree);
+
+        let mut writer = PgnWriter::new(Vec::new());
+        writer.write_game(&game).unwrap();
+        let pgn = String::from_utf8(writer.into_inner()).unwrap();
+        assert!(
+            pgn.contains("{[%eval 0.2]} {Start} 1. e4 {[%clk 0:03:00] [%eval 0.3]} {Best by test}"),
+            "{pgn}"
+        );
+        let reread = PgnReader::new(pgn.as_bytes()).next().unwrap().unwrap();
+        assert_eq!(GameTree::try_from(&reread).unwrap().nodes, tree.nodes);
+    }
+
+    #[test]
+    fn reorders_and_deletes_variations() {
+        let mut tree = tree();
+        let e4 = tree.mainline()[0];
+        let [e5, c5, e6] = tree.node(e4).unwrap().children().try_into().unwrap();
+
+        assert!(tree.promote(c5));
+        assert_eq!(tree.node(e4).unwrap().children(), [c5, e5, e6]);
+        assert!(!tree.promote(c5));
+        assert_eq!(sans(&tree, &tree.mainline()), ["e4", "c5", "Nf3", "d6"]);
+
+        assert!(tree.demote(c5));
+        assert!(tree.demote(c5));
+        assert!(!tree.demote(c5));
+        assert_eq!(tree.node(e4).unwrap().children(), [e5, e6, c5]);
+
+        assert!(tree.promote_to_main(e6));
+        assert_eq!(tree.node(e4).unwrap().children(), [e6, e5, c5]);
+
+        let nf3 = tree.node(c5).unwrap().children()[0];
+        assert!(tree.delete(c5));
+        assert!(!tree.contains(c5) && !tree.contains(nf3));
+        assert_eq!(tree.node(e4).unwrap().children(), [e6, e5]);
+        assert!(!tree.delete(c5));
+        assert!(!tree.delete(tree.root()));
+
+        // deleted ids are reported, never acted upon
+        assert_eq!(tree.node(nf3), None);
+        assert_eq!(tree.position(nf3), None);
+        assert_eq!(tree.moves_to(c5), None);
+        assert!(!tree.promote(c5) && !tree.promote_to_main(c5) && !tree.demote(c5));
+        let e4 = tree.root_position().parse_san("e4");
+        assert_eq!(tree.add_move(nf3, e4.unwrap()), None);
+    }
+
+    #[test]
+    fn rejects_illegal_moves() {
+        let mut tree = GameTree::new(Position::startpos());
+        let root = tree.root();
+        let e4 = tree.root_position().parse_san("e4").unwrap();
+        let e4 = tree.add_move(root, e4).unwrap();
+        // from an empty square, and a white move with black to move
+        let empty = Move::new(Square::E4, Square::E5, MoveFlag::Quiet);
+        assert_eq!(tree.add_move(root, empty), None);
+        let d4 = Move::new(Square::D2, Square::D4, MoveFlag::DoublePawnPush);
+        assert_eq!(tree.add_move(e4, d4), None);
+        assert_eq!(tree.node(root).unwrap().children(), [e4]);
+        assert!(tree.node(e4).unwrap().children().is_empty());
+        assert!(tree.position(e4).is_some());
+    }
+
+    #[test]
+    fn walks_to_any_node() {
+        let mut tree = GameTree::new("4k3/8/8/8/8/8/8/4K2R w K - 0 1".parse().unwrap());
+        let root = tree.root();
+        let castle = tree.root_position().parse_san("O-O").unwrap();
+        let castle = tree.add_move(root, castle).unwrap();
+        let reply = tree.position(castle).unwrap().parse_san("Kd7").unwrap();
+        let reply = tree.add_move(castle, reply).unwrap();
+        let mv = tree.node(castle).unwrap().mv().unwrap();
+        assert_eq!(tree.add_move(root, mv), Some(castle));
+        assert_eq!(
+            tree.position(reply).unwrap().to_string(),
+            "8/3k4/8/8/8/8/8/5RK1 w - - 2 2"
+        );
+        assert_eq!(tree.position(root), Some(*tree.root_position()));
+        assert_eq!(tree.moves_to(reply).map(|moves| moves.len()), Some(2));
+
+        let game = Game::from(&tree);
+        assert_eq!(game.tag("FEN"), Some("4k3/8/8/8/8/8/8/4K2R w K - 0 1"));
+        let reread = GameTree::try_from(&game).unwrap();
+        assert_eq!(reread, tree);
+        assert!(reread.tags.is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+//! Perft regression tests.
+//!
+//! The fast tier runs with every `cargo test`. The deep tier takes minutes in a debug build;
+//! run it with `cargo test --release -- --ignored`.
+//!
+//! `data/random.epd` and `data/chess960.epd` hold positions reached by random play, counted by
+//! a small program over shakmaty 0.29.4 rather than by this crate. For each position it plays
+//! 1 to 120 random legal moves from a start position with a seeded xorshift generator, preferring
+//! captures, promotions and castles a third of the time, skips duplicates and positions with no
+//! legal moves, and prints the FEN with its perft counts to depth 4. The headers of the files
+//! give the exact commands.
+use chess::perft::perft;
+use chess::repr::position::Position;
+
+/// The standard perft positions and their node counts, from depth 1.
+const STANDARD: [(&str, &[u64]); 7] = [
+    (
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        &[20, 400, 8_902, 197_281, 4_865_609, 119_060_324],
+    ),
+    (
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        &[48, 2_039, 97_862, 4_085_603, 193_690_690],
+    ),
+    (
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        &[14, 191, 2_812, 43_238, 674_624, 11_030_083, 178_633_661],
+    ),
+    (
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        &[6, 264, 9_467, 422_333, 15_833_292, 706_045_033],
+    ),
+    (
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+        &[6, 264, 9_467, 422_333, 15_833_292, 706_045_033],
+    ),
+    (
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        &[44, 1_486, 62_379, 2_103_487, 89_941_194],
+    ),
+    (
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        &[46, 2_079, 89_890, 3_894_594, 164_075_551],
+    ),
+];
+
+/// The largest perft count the fast tier computes.
+const FAST_NODE_LIMIT: u64 = 1_000_000;
+
+fn check(fen: &str, counts: impl IntoIterator<Item = (u32, u64)>) {
+    let mut position: Position = fen.parse().unwrap_or_else(|err| panic!("{fen}: {err}"));
+    for (depth, expected) in counts {
+        assert_eq!(perft(&mut position, depth), expected, "{fen} depth {depth}");
+    }
+}
+
+fn depths(counts: &[u64]) -> impl Iterator<Item = (u32, u64)> + '_ {
+    (1..).zip(counts.iter().copied())
+}
+
+/// Parses an EPD perft file into FENs and their `;D<depth> <nodes>` counts.
+fn epd_entries(epd: &str) -> Vec<(String, Vec<(u32, u64)>)> {
+    epd.lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut fields = line.split(';');
+            let fen = fields.next().unwrap().trim().to_owned();
+            let counts = fields
+                .map(|field| {
+                    let (depth, nodes) = field.trim().split_once(' ').unwrap();
+                    (
+                        depth.trim_start_matches('D').parse().unwrap(),
+                        nodes.parse().unwrap(),
+                    )
+                })
+                .collect();
+            (fen, counts)
+        })
+        .collect()
+}
+
+#[test]
+fn standard_positions() {
+    for (fen, counts) in STANDARD {
+        check(fen, depths(counts).filter(|&(_, n)| n <= FAST_NODE_LIMIT));
+    }
+}
+
+#[test]
+#[ignore = "deep perft; run in release mode"]
+fn standard_positions_deep() {
+    for (fen, counts) in STANDARD {
+        check(fen, depths(counts).filter(|&(_, n)| n > FAST_NODE_LIMIT));
+    }
+}
+
+#[test]
+fn epd_suite() {
+    let entries = epd_entries(include_str!("data/random.epd"));
+    assert!(entries.len() >= 400);
+    for (fen, counts) in entries {
+        check(&fen, counts.into_iter().filter(|&(depth, _)| depth <= 3));
+    }
+}
+
+#[test]
+#[ignore = "deep perft; run in release mode"]
+fn epd_suite_deep() {
+    for (fen, counts) in epd_entries(include_str!("data/random.epd")) {
+        check(&fen, counts.into_iter().filter(|&(depth, _)| depth > 3));
+    }
+}
+
+#[test]
+fn chess960_suite() {
+    let entries = epd_entries(include_str!("data/chess960.epd"));
+    assert!(entries.len() >= 400);
+    for (fen, counts) in entries {
+        check(&fen, counts.into_iter().filter(|&(depth, _)| depth <= 3));
+    }
+}
+
+#[test]
+fn chess960_uci_is_unique() {
+    for (fen, _) in epd_entries(include_str!("data/chess960.epd")) {
+        let position: Position = fen.parse().unwrap();
+        let mut uci: Vec<String> = position
+            .legal_moves()
+            .iter()
+            .map(|mv| mv.to_string())
+            .collect();
+        uci.sort();
+        let len = uci.len();
+        uci.dedup();
+        assert_eq!(uci.len(), len, "{fen}");
+    }
+}
+
+#[test]
+#[ignore = "deep perft; run in release mode"]
+fn chess960_suite_deep() {
+    for (fen, counts) in epd_entries(include_str!("data/chess960.epd")) {
+        check(&fen, counts.into_iter().filter(|&(depth, _)| depth > 3));
+    }
+}